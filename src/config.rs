@@ -1,16 +1,20 @@
 use std::borrow::Cow;
 use std::fmt::{Display, Write};
 use std::io::{ErrorKind, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::{env, fmt, fs, io, panic, process};
 
 use ahash::HashSet;
 use anyhow::{anyhow, Context};
-use clap::{command, crate_description, Arg, ArgMatches};
+use clap::parser::ValueSource;
+use clap::{command, crate_description, value_parser, Arg, ArgAction, ArgMatches, Command};
 use directories::{BaseDirs, ProjectDirs};
+use is_executable::IsExecutable;
 use is_terminal::IsTerminal;
 use toml::{map::Map, Value};
 
+use crate::diagnostic;
 use crate::imstr::ImStr;
 use crate::style::{bold, style_stderr, style_stdout};
 
@@ -35,63 +39,528 @@ pub fn get() -> anyhow::Result<Config> {
         .context("could not access config or cache directories")?;
     let base_dirs = BaseDirs::new().expect("unreachable");
     let args = parse_args(&dirs);
+    let targets = read_patterns(&args)?;
+    build(targets, args, dirs, base_dirs)
+}
 
-    let config = if let Some(path) = args.get_one::<String>("PATTERN") {
-        fs::read_to_string(path).context(format!(
-            "unable to read config file `{}`",
-            style_stderr!(bold(), "{path}")
-        ))?
-    } else {
-        let mut buf = String::new();
-        io::stdin()
-            .read_to_string(&mut buf)
-            .context("unable to read piped input")?;
-        buf
+/// Read the patterns given as arguments, or else the pattern piped through stdin.
+fn read_patterns(args: &ArgMatches) -> anyhow::Result<Vec<(ConfigSource, Source)>> {
+    let check = args.subcommand_matches("check");
+    let patterns = check
+        .unwrap_or(args)
+        .get_many::<String>("PATTERN")
+        .into_iter()
+        .flatten();
+
+    let mut targets = Vec::new();
+    let mut read_stdin = false;
+    for path in patterns {
+        if path == "-" {
+            if read_stdin {
+                return Err(anyhow!(
+                    "`{}` may only be given once, since stdin can only be read once",
+                    style_stderr!(bold(), "-")
+                ));
+            }
+            read_stdin = true;
+            targets.push((ConfigSource::Target(None), read_piped()?));
+            continue;
+        }
+
+        targets.push(read_pattern(Path::new(path))?);
+    }
+
+    // A pattern is picked from a directory after the other configs are read.
+    if targets.is_empty() && !args.contains_id("pick") {
+        let file = if check.is_some() && io::stdin().is_terminal() {
+            // Only the other configs are checked if no pattern is given.
+            Source {
+                path: String::from("<stdin>"),
+                text: String::new(),
+            }
+        } else {
+            read_piped()?
+        };
+        targets.push((ConfigSource::Target(None), file));
+    }
+
+    Ok(targets)
+}
+
+fn read_pattern(path: &Path) -> anyhow::Result<(ConfigSource, Source)> {
+    let text = fs::read_to_string(path).context(format!(
+        "unable to read config file `{}`",
+        style_stderr!(bold(), "{}", path.display())
+    ))?;
+    let file = Source {
+        path: path.display().to_string(),
+        text,
     };
-    let config = config
+    Ok((ConfigSource::Target(Some(path.to_owned())), file))
+}
+
+/// Combine `targets` with every other config.
+fn build(
+    targets: Vec<(ConfigSource, Source)>,
+    args: ArgMatches,
+    dirs: ProjectDirs,
+    base_dirs: BaseDirs,
+) -> anyhow::Result<Config> {
+    let mut layers = Vec::new();
+    let mut sources = Sources::default();
+    let files = targets.into_iter().chain(read_configs(&dirs)?);
+    for (source, file) in files {
+        load(
+            source,
+            file,
+            &base_dirs,
+            &mut Vec::new(),
+            &mut layers,
+            &mut sources,
+        )
+        .map_err(|mut err| {
+            sources.annotate(&mut err);
+            err
+        })?;
+    }
+
+    let profile = args.get_one::<String>("profile").map(String::as_str);
+    apply_profile(&mut layers, profile).map_err(|mut err| {
+        sources.annotate(&mut err);
+        err
+    })?;
+    let env_sets = env::var("DMM_SET").ok();
+    if let Some(config) = parse_overrides(&args, env_sets.as_deref())? {
+        layers.insert(
+            0,
+            Layer {
+                source: ConfigSource::Args,
+                config,
+            },
+        );
+    }
+
+    match Config::try_new(&layers, args, dirs, base_dirs) {
+        Ok(mut config) => {
+            sources.annotate_unknown_keys(&mut config.unknown_keys);
+            Ok(config)
+        }
+        Err(mut err) => {
+            sources.annotate(&mut err);
+            Err(err)
+        }
+    }
+}
+
+/// Read a pattern piped through stdin.
+fn read_piped() -> anyhow::Result<Source> {
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .context("unable to read piped input")?;
+    Ok(Source {
+        path: String::from("<stdin>"),
+        text,
+    })
+}
+
+/// Parse a config, showing where in the file any syntax error is.
+fn parse_source(source: &Source) -> anyhow::Result<Value> {
+    source
+        .text
         .parse::<Value>()
-        .context("found incorrect formatting in target config")?;
-
-    let home_config = read_home_config(dirs.config_dir())?;
-    let home_config = home_config.map(|config| {
-        config.parse::<Value>().context(format!(
-            "found incorrect formatting in home config `{}`",
-            style_stderr!(
-                bold(),
-                "{}",
-                dirs.config_dir().join("config.toml").display()
-            )
-        ))
+        .map_err(|err| match err.line_col() {
+            Some((line, column)) => {
+                let offset = diagnostic::offset(&source.text, line, column);
+                let snippet = diagnostic::snippet(&source.path, &source.text, offset..offset + 1);
+                anyhow!("{err}\n{snippet}")
+            }
+            None => anyhow::Error::new(err),
+        })
+}
+
+/// Parse the config `file`, then every config it includes,
+/// adding each to `layers` in order of decreasing precedence.
+///
+/// A config takes precedence over the configs it includes,
+/// and a later include takes precedence over an earlier one.
+/// `including` holds the canonical path of every config that's being loaded, to detect cycles.
+fn load(
+    source: ConfigSource,
+    file: Source,
+    base_dirs: &BaseDirs,
+    including: &mut Vec<PathBuf>,
+    layers: &mut Vec<Layer>,
+    sources: &mut Sources,
+) -> anyhow::Result<()> {
+    let config = parse_source(&file).context(format!("found incorrect formatting in {source}"))?;
+
+    let canonical = fs::canonicalize(&file.path).ok();
+    let dir = Path::new(&file.path)
+        .parent()
+        .map(Path::to_owned)
+        .unwrap_or_default();
+    sources.0.push((source.clone(), file));
+
+    let includes = config
+        .get("include")
+        .map(try_into_array("include"))
+        .transpose()
+        .context(SourceProblem(source.clone()))?
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, include)| {
+            let key = format!("include[{i}]");
+            let include = try_into_array_string("include")(include)?;
+            Ok((key, include))
+        })
+        .collect::<anyhow::Result<Vec<(String, ImStr)>>>()
+        .context(SourceProblem(source.clone()))?;
+
+    including.extend(canonical.clone());
+    layers.push(Layer {
+        source: source.clone(),
+        config,
     });
-    let home_config = if let Some(home_config) = home_config {
-        Some(home_config?)
-    } else {
-        None
+
+    for (key, include) in includes.into_iter().rev() {
+        let path = match include.strip_prefix("~/") {
+            Some(include) => base_dirs.home_dir().join(include),
+            None => dir.join(include.as_str()),
+        };
+
+        let text = fs::read_to_string(&path)
+            .map_err(|err| {
+                key_error(
+                    &key,
+                    format!(
+                        "unable to read included config `{}`: {err}",
+                        style_stderr!(bold(), "{}", path.display())
+                    ),
+                )
+            })
+            .context(SourceProblem(source.clone()))?;
+
+        if fs::canonicalize(&path).is_ok_and(|path| including.contains(&path)) {
+            return Err(key_error(
+                &key,
+                format!(
+                    "including `{}` would create a cycle, since it includes this config",
+                    style_stderr!(bold(), "{}", path.display())
+                ),
+            ))
+            .context(SourceProblem(source));
+        }
+
+        let file = Source {
+            path: path.display().to_string(),
+            text,
+        };
+        load(
+            ConfigSource::Include(path),
+            file,
+            base_dirs,
+            including,
+            layers,
+            sources,
+        )?;
+    }
+
+    if canonical.is_some() {
+        including.pop();
+    }
+    Ok(())
+}
+
+/// Collect the config values set by command line arguments and their environment variables,
+/// as a config with higher precedence than any other.
+/// `env_sets` is the value of `$DMM_SET`, whose values are overridden by any arguments.
+fn parse_overrides(args: &ArgMatches, env_sets: Option<&str>) -> anyhow::Result<Option<Value>> {
+    let is_set = |id| {
+        matches!(
+            args.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
     };
 
-    Config::try_new(&config, home_config.as_ref(), args, dirs, base_dirs)
-}
-
-fn read_home_config(dirs: &Path) -> anyhow::Result<Option<String>> {
-    let config_path = dirs.join("config.toml");
-    let result = fs::read_to_string(&config_path);
-    match result {
-        Ok(config) => Ok(Some(config)),
-        Err(err) => {
-            if err.kind() == ErrorKind::NotFound {
-                Ok(None)
-            } else {
-                Err(err).context(format!(
-                    "unable to read home config file `{}`",
-                    style_stderr!(bold(), "{}", config_path.display())
-                ))
+    let mut dmenu = Map::new();
+    if let Some(prompt) = args.get_one::<String>("prompt") {
+        dmenu.insert(String::from("prompt"), Value::String(prompt.clone()));
+    }
+    if let Some(lines) = args.get_one::<i64>("lines") {
+        dmenu.insert(String::from("lines"), Value::Integer(*lines));
+    }
+    if is_set("bottom") {
+        dmenu.insert(
+            String::from("bottom"),
+            Value::Boolean(args.get_flag("bottom")),
+        );
+    }
+
+    let mut config = Map::new();
+    if !dmenu.is_empty() {
+        config.insert(String::from("dmenu"), Value::Table(dmenu));
+    }
+    if let Some(shell) = args.get_one::<String>("shell") {
+        let shell = shell
+            .split_whitespace()
+            .map(String::from)
+            .map(Value::String);
+        config.insert(String::from("shell"), Value::Array(shell.collect()));
+    }
+
+    let mut overrides = Map::new();
+    if let Some(sets) = env_sets {
+        for set in sets.lines().filter(|set| !set.trim().is_empty()) {
+            let value = parse_set(set).context(format!(
+                "invalid value `{}` in `{}`",
+                style_stderr!(bold(), "{set}"),
+                style_stderr!(bold(), "$DMM_SET")
+            ))?;
+            merge_tables(&mut overrides, value);
+        }
+    }
+    if !config.is_empty() {
+        merge_tables(
+            &mut overrides,
+            Map::from_iter([(String::from("config"), Value::Table(config))]),
+        );
+    }
+    for set in args.get_many::<String>("set").into_iter().flatten() {
+        let value = parse_set(set).context(format!(
+            "invalid value `{}` for `{}`",
+            style_stderr!(bold(), "{set}"),
+            style_stderr!(bold(), "--set")
+        ))?;
+        merge_tables(&mut overrides, value);
+    }
+
+    Ok((!overrides.is_empty()).then_some(Value::Table(overrides)))
+}
+
+/// Parse a `KEY=VALUE` pair from `--set` into a table containing only that key.
+///
+/// The key may be a dotted toml key path. The value is parsed as toml if possible,
+/// and is otherwise a string, so strings don't need to be quoted.
+fn parse_set(set: &str) -> anyhow::Result<Map<String, Value>> {
+    let (key, value) = set
+        .split_once('=')
+        .context("must be written as `KEY=VALUE`")?;
+    let value = format!("value = {value}")
+        .parse::<Value>()
+        .ok()
+        .and_then(|mut table| table.as_table_mut()?.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_owned()));
+
+    // Parse the key with a placeholder value, which is then replaced.
+    let mut table = format!("{key} = 0")
+        .parse::<Value>()
+        .context("the key isn't a valid toml key")?;
+    let path_error = || {
+        anyhow!(
+            "the key must be a single toml key path, such as `{}`",
+            style_stderr!(bold(), "config.dmenu.monitor")
+        )
+    };
+    let mut leaf = &mut table;
+    while let Value::Table(table) = leaf {
+        let mut entries = table.iter_mut();
+        let (Some((_, next)), None) = (entries.next(), entries.next()) else {
+            return Err(path_error());
+        };
+        leaf = next;
+    }
+    if *leaf != Value::Integer(0) {
+        return Err(path_error());
+    }
+    *leaf = value;
+
+    match table {
+        Value::Table(table) => Ok(table),
+        _ => unreachable!("toml documents are tables"),
+    }
+}
+
+/// Recursively merge `from` into `into`, replacing any value that isn't a table in both.
+fn merge_tables(into: &mut Map<String, Value>, from: Map<String, Value>) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(Value::Table(into)), Value::Table(from)) => merge_tables(into, from),
+            (_, value) => {
+                into.insert(key, value);
             }
         }
     }
 }
 
-fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
-    let args = command!()
+/// Add the profile named `selected`, or else the first profile whose conditions match,
+/// as a layer with precedence just above the config it's defined in.
+fn apply_profile(layers: &mut Vec<Layer>, selected: Option<&str>) -> anyhow::Result<()> {
+    let mut hostname = None;
+    let mut found = None;
+
+    'layers: for (i, layer) in layers.iter().enumerate() {
+        let profiles = layer
+            .config
+            .get("profile")
+            .map(try_into_table("profile"))
+            .transpose()
+            .context(SourceProblem(layer.source.clone()))?;
+
+        for (name, profile) in profiles.into_iter().flatten() {
+            let key = format!("profile.{name}");
+            let profile =
+                try_into_table(&key)(profile).context(SourceProblem(layer.source.clone()))?;
+            // An explicitly selected profile is found by name alone,
+            // so the conditions of the other profiles are never checked.
+            let matches = match selected {
+                Some(selected) => selected == name,
+                None => profile_matches(&key, profile, &mut hostname)
+                    .context(SourceProblem(layer.source.clone()))?,
+            };
+
+            if matches {
+                let config = profile
+                    .get("config")
+                    .map(try_into_table(&format!("{key}.config")))
+                    .transpose()
+                    .context(SourceProblem(layer.source.clone()))?
+                    .cloned()
+                    .unwrap_or_default();
+                found = Some((i, name.clone(), config));
+                break 'layers;
+            }
+        }
+    }
+
+    match found {
+        Some((i, name, config)) => {
+            let source = ConfigSource::Profile(name, Box::new(layers[i].source.clone()));
+            let config = Value::Table(Map::from_iter([(
+                String::from("config"),
+                Value::Table(config),
+            )]));
+            layers.insert(i, Layer { source, config });
+            Ok(())
+        }
+        None => match selected {
+            Some(selected) => Err(anyhow!(
+                "no profile named `{}` was found",
+                style_stderr!(bold(), "{selected}")
+            )),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Whether every condition in the `when` table of `profile` is met.
+/// A profile without conditions is only used if it's selected by name.
+///
+/// `hostname` caches the hostname, which is only looked up if a profile needs it.
+fn profile_matches(
+    key: &str,
+    profile: &Map<String, Value>,
+    hostname: &mut Option<Option<String>>,
+) -> anyhow::Result<bool> {
+    let Some(when) = profile.get("when") else {
+        return Ok(false);
+    };
+    let when = try_into_table(&format!("{key}.when"))(when)?;
+
+    let mut matches = true;
+    if let Some(hostnames) = when.get("hostname") {
+        let name = format!("{key}.when.hostname");
+        let hostnames = match hostnames {
+            Value::String(hostname) => vec![ImStr::from(hostname)],
+            Value::Array(hostnames) => hostnames
+                .iter()
+                .map(try_into_array_string(&name))
+                .collect::<Result<Vec<ImStr>, _>>()?,
+            other => return type_error(&name, &["string", "array"], other.type_str()),
+        };
+        let hostname = hostname.get_or_insert_with(get_hostname);
+        matches &= hostname
+            .as_deref()
+            .is_some_and(|hostname| hostnames.iter().any(|name| name.as_str() == hostname));
+    }
+    if let Some(var) = when.get("env") {
+        let var = try_into_string(&format!("{key}.when.env"))(var)?;
+        matches &= env::var_os(var.as_str()).is_some();
+    }
+
+    Ok(matches)
+}
+
+/// The name of this machine, if it can be determined.
+fn get_hostname() -> Option<String> {
+    let mut buffer = [0_u8; 256];
+    // SAFETY: the buffer is valid for writes of its whole length.
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = buffer
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(buffer.len());
+    String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+/// Read every config other than the pattern, in order of decreasing precedence:
+/// the config at `$DMM_CONFIG`, the home config,
+/// then the config in each directory of `$XDG_CONFIG_DIRS`, and finally in `/etc/xdg`.
+fn read_configs(dirs: &ProjectDirs) -> anyhow::Result<Vec<(ConfigSource, Source)>> {
+    let mut configs = Vec::new();
+
+    if let Some(path) = env::var_os("DMM_CONFIG").filter(|path| !path.is_empty()) {
+        let path = PathBuf::from(path);
+        let text = fs::read_to_string(&path).context(format!(
+            "unable to read config file `{}` from `{}`",
+            style_stderr!(bold(), "{}", path.display()),
+            style_stderr!(bold(), "$DMM_CONFIG")
+        ))?;
+        let file = Source {
+            path: path.display().to_string(),
+            text,
+        };
+        configs.push((ConfigSource::Env(path), file));
+    }
+
+    // Unlike `$DMM_CONFIG`, these configs are only read if they exist.
+    let mut optional = vec![ConfigSource::Home(dirs.config_dir().join("config.toml"))];
+    let system_dirs = env::var_os("XDG_CONFIG_DIRS")
+        .map(|dirs| env::split_paths(&dirs).collect::<Vec<PathBuf>>())
+        .unwrap_or_default();
+    let mut seen_dirs = HashSet::default();
+    for dir in system_dirs.into_iter().chain([PathBuf::from("/etc/xdg")]) {
+        // Relative paths are invalid in `$XDG_CONFIG_DIRS`, and should be ignored.
+        if dir.is_absolute() && seen_dirs.insert(dir.clone()) {
+            optional.push(ConfigSource::System(dir.join("dmm").join("config.toml")));
+        }
+    }
+
+    for source in optional {
+        let path = source.path().expect("optional configs are files");
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let file = Source {
+                    path: path.display().to_string(),
+                    text,
+                };
+                configs.push((source, file));
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err).context(format!("unable to read {source}")),
+        }
+    }
+
+    Ok(configs)
+}
+
+/// The command line interface, with every argument and subcommand.
+fn cli(dirs: &ProjectDirs) -> Command {
+    command!()
         .about(concat!(crate_description!(), ".\n"))
         .long_about(format!(
             concat!(
@@ -99,7 +568,8 @@ fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
                 ".\n",
                 "The toml config may be piped in instead of specifying a file path.\n",
                 "A config may be written at `{}/config.toml`.\n",
-                "This will define default options that are overridden by the main pattern."
+                "This will define default options that are overridden by the main pattern.\n",
+                "Configs in `/etc/xdg/dmm` and `$XDG_CONFIG_DIRS`, and at `$DMM_CONFIG`, are also read."
             ),
             dirs.config_dir().display()
         ))
@@ -110,14 +580,153 @@ fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
         )
         .arg({
             Arg::new("PATTERN")
-                .help("Path to a pattern file")
+                .help("Paths to pattern files, or `-` to read a pattern from stdin")
                 .long_help(
-                    "Path to a pattern file.\n\
+                    "Paths to pattern files, or `-` to read a pattern from stdin.\n\
                      Either this must be specified, or the pattern must be piped in.\n\
-                     If specified, anything piped through stdin is ignored.",
+                     If specified, anything piped through stdin is ignored unless `-` is given.\n\
+                     The menus of every pattern are combined; if several patterns\n\
+                     have an entry or setting in common, the earliest one is used.",
                 )
                 .index(1)
+                .num_args(1..)
         })
+        .arg(
+            Arg::new("print")
+                .help("Print the selected commands instead of running them")
+                .long_help(
+                    "Print the selected commands to stdout instead of running them.\n\
+                     `text` prints each command as written in the pattern.\n\
+                     `json` prints a json object for each command on its own line,\n\
+                     including the program and arguments that would be spawned.",
+                )
+                .long("print")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"])
+                .num_args(0..=1)
+                .default_missing_value("text"),
+        )
+        .arg(
+            Arg::new("pick")
+                .help("Choose a pattern from a directory in the launcher, then run it")
+                .long_help(format!(
+                    "Choose a pattern from a directory in the launcher, then run it.\n\
+                     Every `.toml` file in the directory is listed by its `title`\n\
+                     and `description`, or else by its file name.\n\
+                     The directory defaults to `{}`.",
+                    dirs.config_dir().join("patterns").display()
+                ))
+                .long("pick")
+                .value_name("DIR")
+                .value_parser(value_parser!(PathBuf))
+                .num_args(0..=1)
+                .conflicts_with("PATTERN"),
+        )
+        .arg(
+            Arg::new("prompt")
+                .help("Override `config.dmenu.prompt`")
+                .long("prompt")
+                .value_name("PROMPT")
+                .env("DMM_PROMPT"),
+        )
+        .arg(
+            Arg::new("lines")
+                .help("Override `config.dmenu.lines`")
+                .long("lines")
+                .value_name("LINES")
+                .value_parser(value_parser!(i64).range(0..))
+                .env("DMM_LINES"),
+        )
+        .arg(
+            Arg::new("bottom")
+                .help("Override `config.dmenu.bottom`")
+                .long("bottom")
+                .action(ArgAction::SetTrue)
+                .env("DMM_BOTTOM"),
+        )
+        .arg(
+            Arg::new("shell")
+                .help("Override `config.shell` with a shell and its arguments, such as \"bash -c\"")
+                .long_help(
+                    "Override `config.shell` with a shell and its arguments, such as \"bash -c\".\n\
+                     The value is split on whitespace, so to pass an argument containing spaces,\n\
+                     use `--set` with an array instead, such as\n\
+                     `config.shell=[\"bash\", \"--rcfile\", \"/path with space/rc\", \"-c\"]`.",
+                )
+                .long("shell")
+                .value_name("SHELL")
+                .env("DMM_SHELL"),
+        )
+        .arg(
+            Arg::new("set")
+                .help("Override any config value, such as `config.dmenu.monitor=1`")
+                .long_help(
+                    "Override any config value, such as `config.dmenu.monitor=1`.\n\
+                     The key is a toml key path, and the value is parsed as toml,\n\
+                     or used as a string if it isn't valid toml.\n\
+                     Values set by arguments override every config, including the pattern.\n\
+                     Values may also be set with `DMM_SET`, one `KEY=VALUE` per line,\n\
+                     which are overridden by other arguments.",
+                )
+                .long("set")
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("profile")
+                .help("Use the settings of a profile defined in a config")
+                .long_help(
+                    "Use the settings of a profile defined in a config.\n\
+                     Otherwise, the first profile whose `when` conditions match is used, if any.",
+                )
+                .long("profile")
+                .value_name("NAME")
+                .env("DMM_PROFILE"),
+        )
+        .arg(
+            Arg::new("show-origin")
+                .help("List which config each entry and setting is taken from, then exit")
+                .long_help(
+                    "List which config each entry and setting is taken from, then exit.\n\
+                     Configs are read in order of increasing precedence from\n\
+                     `/etc/xdg/dmm/config.toml`, each directory in `$XDG_CONFIG_DIRS`,\n\
+                     the home config, the file at `$DMM_CONFIG`, then the pattern.",
+                )
+                .long("show-origin")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .help("Describe what would be executed instead of running it")
+                .long_help(
+                    "Describe what would be executed instead of running it.\n\
+                     Each selected command is logged to stderr,\n\
+                     along with the shell it would be run by and whether the shell is piped.",
+                )
+                .long("dry-run")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Check a pattern and every other config for problems without running anything")
+                .long_about(
+                    "Check a pattern and every other config for problems without running anything.\n\
+                     Reports any invalid values, and any keys that dmm doesn't recognize.\n\
+                     Exits with a non-zero status if any problems are found.",
+                )
+                .arg(
+                    Arg::new("PATTERN")
+                        .help("Paths to pattern files, or `-` to read a pattern from stdin")
+                        .long_help(
+                            "Paths to pattern files, or `-` to read a pattern from stdin.\n\
+                             If not specified, the pattern may be piped in.\n\
+                             Otherwise, only the other configs are checked.",
+                        )
+                        .index(1)
+                        .num_args(1..),
+                ),
+        )
+        .args_conflicts_with_subcommands(true)
         .after_help(format!(
             "{}\n{}",
             style_stdout!(bold().set_underline(true), "Example Pattern:"),
@@ -127,7 +736,11 @@ fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
             "{}\n{}",
             style_stdout!(bold().set_underline(true), "Example Pattern:"),
             LONG_EXAMPLE
-        ));
+        ))
+}
+
+fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
+    let args = cli(dirs);
     let args = if io::stdin().is_terminal() {
         args.arg_required_else_help(true)
     } else {
@@ -148,12 +761,90 @@ fn parse_args(dirs: &ProjectDirs) -> ArgMatches {
 pub enum Run {
     Shell(ImStr),
     Bare(Vec<ImStr>),
+    /// Text to copy to the clipboard with `config.clipboard`.
+    Copy(ImStr),
+    /// Text to type into the focused window.
+    Type(ImStr),
+    /// A path or url to open with the default application.
+    Open(ImStr),
 }
 
 impl Run {
     pub fn binary(run: ImStr) -> Self {
         Self::Bare(vec![run])
     }
+
+    fn try_new(key: &str, run: &Value) -> anyhow::Result<Self> {
+        match run {
+            Value::String(run) => Ok(Self::Shell(ImStr::from(run))),
+            Value::Array(run) => Ok(Self::Bare(
+                run.iter()
+                    .map(try_into_array_string(key))
+                    .collect::<Result<Vec<ImStr>, _>>()?,
+            )),
+            other => type_error(key, &["string", "array"], other.type_str()),
+        }
+    }
+
+    /// Replace every occurrence of each placeholder with its value, in a single pass,
+    /// so a placeholder inside a substituted value is left as is.
+    ///
+    /// In a shell command, values are quoted so the shell treats each as a single word.
+    /// In a bare command, or text to copy, type, or open, values are inserted as is.
+    pub fn substitute(&self, values: &[(&str, &str)]) -> Self {
+        let replace = |text: &ImStr| ImStr::from(replace_all(text, values, |value| value.into()));
+        match self {
+            Self::Shell(run) => Self::Shell(ImStr::from(replace_all(run, values, |value| {
+                shell_quote(value).into()
+            }))),
+            Self::Bare(run) => Self::Bare(run.iter().map(replace).collect()),
+            Self::Copy(text) => Self::Copy(replace(text)),
+            Self::Type(text) => Self::Type(replace(text)),
+            Self::Open(target) => Self::Open(replace(target)),
+        }
+    }
+
+    /// The name of the kind of action, as written in the `--print` json output.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Shell(_) => "shell",
+            Self::Bare(_) => "bare",
+            Self::Copy(_) => "copy",
+            Self::Type(_) => "type",
+            Self::Open(_) => "open",
+        }
+    }
+}
+
+/// Replace every placeholder in `text` with its value, transformed by `transform`,
+/// scanning from left to right so replaced text is never scanned again.
+fn replace_all<'a>(
+    text: &str,
+    values: &[(&str, &'a str)],
+    transform: impl Fn(&'a str) -> Cow<'a, str>,
+) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let found = values
+            .iter()
+            .find(|(placeholder, _)| !placeholder.is_empty() && rest.starts_with(placeholder));
+        if let Some((placeholder, value)) = found {
+            replaced.push_str(&transform(value));
+            rest = &rest[placeholder.len()..];
+        } else {
+            replaced.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    replaced
+}
+
+/// Quote `value` so a posix shell treats it as a single word.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
 }
 
 impl Display for Run {
@@ -170,19 +861,63 @@ impl Display for Run {
                     Ok(())
                 }
             },
+            Self::Copy(text) => write!(f, "copy {text}"),
+            Self::Type(text) => write!(f, "type {text}"),
+            Self::Open(target) => write!(f, "open {target}"),
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum Entry {
-    Full { name: ImStr, run: Run, group: i64 },
+    Full {
+        name: ImStr,
+        run: Run,
+        group: i64,
+        description: Option<ImStr>,
+        keywords: Rc<[ImStr]>,
+        args: Rc<[Argument]>,
+        options: RunOptions,
+    },
+    Menu {
+        name: ImStr,
+        menu: Rc<Submenu>,
+        group: i64,
+        description: Option<ImStr>,
+        keywords: Rc<[ImStr]>,
+    },
     Name(ImStr),
     Filter(ImStr),
 }
 
 impl Entry {
-    fn try_new(name: ImStr, entry: &Value) -> anyhow::Result<Self> {
+    const KEYS: &'static [&'static str] = &[
+        "run",
+        "copy",
+        "type",
+        "open",
+        "group",
+        "description",
+        "keywords",
+        "args",
+        "menu",
+        "prompt",
+        "back",
+        "terminal",
+        "cwd",
+        "env",
+        "wait",
+        "output",
+        "each",
+    ];
+
+    /// The keys that set what an entry does when it's selected, at most one of which may be set.
+    const ACTION_KEYS: &'static [&'static str] = &["run", "copy", "type", "open", "menu"];
+
+    /// Parse the entry `name` found in the table at the key path `parent`.
+    fn try_new(parent: &str, name: ImStr, entry: &Value) -> anyhow::Result<Self> {
+        let key = format!("{parent}.{name}");
+
         match entry {
             Value::Boolean(true) => Ok(Self::Name(name)),
             Value::Boolean(false) => Ok(Self::Filter(name)),
@@ -190,31 +925,140 @@ impl Entry {
                 name,
                 run: Run::Shell(ImStr::from(run)),
                 group: 0,
+                description: None,
+                keywords: Rc::default(),
+                args: Rc::default(),
+                options: RunOptions::default(),
             }),
             Value::Array(run) => {
                 let run = run
                     .iter()
-                    .map(try_into_array_string(&format!("menu.{name}")))
+                    .map(try_into_array_string(&key))
                     .collect::<Result<Vec<ImStr>, _>>()?;
 
                 Ok(Self::Full {
                     name,
                     run: Run::Bare(run),
                     group: 0,
+                    description: None,
+                    keywords: Rc::default(),
+                    args: Rc::default(),
+                    options: RunOptions::default(),
                 })
             }
             Value::Table(table) => {
+                let mut actions = Self::ACTION_KEYS
+                    .iter()
+                    .filter(|action| table.contains_key(**action));
+                if let (Some(first), Some(second)) = (actions.next(), actions.next()) {
+                    return Err(key_error(
+                        &format!("{key}.{second}"),
+                        format!(
+                            "`{}` and `{}` can't both have a value",
+                            style_stderr!(bold(), "{key}.{first}"),
+                            style_stderr!(bold(), "{key}.{second}"),
+                        ),
+                    ));
+                }
+
                 let group = table
                     .get("group")
-                    .map(try_into_integer(&format!("menu.{name}.group")))
+                    .map(try_into_integer(&format!("{key}.group")))
                     .transpose()?
                     .unwrap_or(0);
 
-                let missing_run_error = format!(
-                    "`{}` must have a value if `{}` is a table",
-                    style_stderr!(bold(), "menu.{name}.run"),
-                    style_stderr!(bold(), "menu.{name}"),
-                );
+                let description = table
+                    .get("description")
+                    .map(try_into_string(&format!("{key}.description")))
+                    .transpose()?;
+
+                let keywords = table
+                    .get("keywords")
+                    .map(try_into_array(&format!("{key}.keywords")))
+                    .transpose()?
+                    .into_iter()
+                    .flatten()
+                    .map(try_into_array_string(&format!("{key}.keywords")))
+                    .collect::<Result<Rc<[ImStr]>, _>>()?;
+
+                if let Some(menu) = table.get("menu") {
+                    let menu = Submenu::try_new(&key, table, menu)?;
+                    return Ok(Self::Menu {
+                        name,
+                        menu: Rc::new(menu),
+                        group,
+                        description,
+                        keywords,
+                    });
+                }
+
+                let args = table
+                    .get("args")
+                    .map(try_into_array(&format!("{key}.args")))
+                    .transpose()?
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(i, arg)| Argument::try_new(&format!("{key}.args[{i}]"), arg))
+                    .collect::<Result<Rc<[Argument]>, _>>()?;
+
+                let mut options = RunOptions::try_new(&key, table)?;
+                options.output = Output::try_new(&key, table)?;
+                if options.terminal && matches!(options.output, Output::Menu { .. }) {
+                    return Err(key_error(
+                        &format!("{key}.output"),
+                        format!(
+                            "`{}` and `{}` can't both be set",
+                            style_stderr!(bold(), "{key}.terminal"),
+                            style_stderr!(bold(), "{key}.output"),
+                        ),
+                    ));
+                }
+
+                let missing_run_error = || {
+                    key_error(
+                        &key,
+                        format!(
+                            "one of `{}`, `{}`, `{}`, `{}`, or `{}` must have a value if `{}` is a table",
+                            style_stderr!(bold(), "{key}.run"),
+                            style_stderr!(bold(), "{key}.copy"),
+                            style_stderr!(bold(), "{key}.type"),
+                            style_stderr!(bold(), "{key}.open"),
+                            style_stderr!(bold(), "{key}.menu"),
+                            style_stderr!(bold(), "{key}"),
+                        ),
+                    )
+                };
+
+                let actions = [
+                    ("copy", Run::Copy as fn(ImStr) -> Run),
+                    ("type", Run::Type),
+                    ("open", Run::Open),
+                ];
+                for (action, new) in actions {
+                    if let Some(text) = table.get(action) {
+                        let text = try_into_string(&format!("{key}.{action}"))(text)?;
+                        if matches!(options.output, Output::Menu { .. }) {
+                            return Err(key_error(
+                                &format!("{key}.output"),
+                                format!(
+                                    "`{}` can only be set with `{}`",
+                                    style_stderr!(bold(), "{key}.output"),
+                                    style_stderr!(bold(), "{key}.run"),
+                                ),
+                            ));
+                        }
+                        return Ok(Self::Full {
+                            name,
+                            run: new(text),
+                            group,
+                            description,
+                            keywords,
+                            args,
+                            options,
+                        });
+                    }
+                }
 
                 table
                     .get("run")
@@ -225,30 +1069,38 @@ impl Entry {
                             name,
                             run: Run::Shell(ImStr::from(run)),
                             group,
+                            description,
+                            keywords,
+                            args,
+                            options,
                         }),
                         Value::Array(run) => {
                             let run = run
                                 .iter()
-                                .map(try_into_array_string(&format!("menu.{name}.run")))
+                                .map(try_into_array_string(&format!("{key}.run")))
                                 .collect::<Result<Vec<ImStr>, _>>()?;
 
                             Ok(Self::Full {
                                 name,
                                 run: Run::Bare(run),
                                 group,
+                                description,
+                                keywords,
+                                args,
+                                options,
                             })
                         }
                         other => type_error(
-                            "menu.{name}.run",
+                            &format!("{key}.run"),
                             &["string", "array", "boolean"],
                             other.type_str(),
                         ),
                     })
                     .transpose()?
-                    .context(missing_run_error)
+                    .ok_or_else(missing_run_error)
             }
             other => type_error(
-                "menu.{name}",
+                &key,
                 &["string", "array", "boolean", "table"],
                 other.type_str(),
             ),
@@ -257,7 +1109,369 @@ impl Entry {
 
     pub fn name(&self) -> ImStr {
         match self {
-            Self::Full { name, .. } | Self::Name(name) | Self::Filter(name) => name.clone(),
+            Self::Full { name, .. }
+            | Self::Menu { name, .. }
+            | Self::Name(name)
+            | Self::Filter(name) => name.clone(),
+        }
+    }
+}
+
+/// Options that control how an entry's run command is executed.
+#[derive(Debug, Default, Clone)]
+pub struct RunOptions {
+    /// Run the command inside the terminal emulator from `config.terminal`.
+    pub terminal: bool,
+    /// The working directory of the command; a leading `~/` is replaced with the home directory.
+    pub cwd: Option<ImStr>,
+    /// Environment variables set for the command, in addition to those from `config.env`.
+    pub env: Vec<(ImStr, ImStr)>,
+    /// Wait for the command to exit, and report it if it fails.
+    pub wait: bool,
+    pub output: Output,
+}
+
+impl RunOptions {
+    /// Parse the options found in the table at the key path `key`.
+    fn try_new(key: &str, table: &Map<String, Value>) -> anyhow::Result<Self> {
+        let terminal = table
+            .get("terminal")
+            .map(try_into_boolean(&format!("{key}.terminal")))
+            .transpose()?
+            .unwrap_or(false);
+
+        let cwd = table
+            .get("cwd")
+            .map(try_into_string(&format!("{key}.cwd")))
+            .transpose()?;
+
+        let env = table
+            .get("env")
+            .map(|env| try_into_env(&format!("{key}.env"), env))
+            .transpose()?
+            .unwrap_or_default();
+
+        let wait = table
+            .get("wait")
+            .map(try_into_boolean(&format!("{key}.wait")))
+            .transpose()?
+            .unwrap_or(false);
+
+        Ok(Self {
+            terminal,
+            cwd,
+            env,
+            wait,
+            output: Output::default(),
+        })
+    }
+}
+
+/// What's done with the output of an entry's run command.
+#[derive(Debug, Default, Clone)]
+pub enum Output {
+    /// The output is discarded, or appended to the log from `config.detach`.
+    #[default]
+    Ignore,
+    /// Each line of output is shown in another menu.
+    /// If `each` is set, it's run for each selected line, with every `{}` replaced by the line.
+    Menu { each: Option<Run> },
+}
+
+impl Output {
+    /// Parse the `output` and `each` keys of the entry table at the key path `key`.
+    fn try_new(key: &str, table: &Map<String, Value>) -> anyhow::Result<Self> {
+        let each = table
+            .get("each")
+            .map(|each| Run::try_new(&format!("{key}.each"), each))
+            .transpose()?;
+
+        let output = table
+            .get("output")
+            .map(try_into_string(&format!("{key}.output")))
+            .transpose()?;
+
+        match output.as_deref() {
+            Some("menu") => Ok(Self::Menu { each }),
+            Some(other) => Err(key_error(
+                &format!("{key}.output"),
+                format!(
+                    "`{}` must be `{}`, but is `{}`",
+                    style_stderr!(bold(), "{key}.output"),
+                    style_stderr!(bold(), "menu"),
+                    style_stderr!(bold(), "{other}")
+                ),
+            )),
+            None if each.is_some() => Err(key_error(
+                &format!("{key}.each"),
+                format!(
+                    "`{}` can only be set if `{}` is `{}`",
+                    style_stderr!(bold(), "{key}.each"),
+                    style_stderr!(bold(), "{key}.output"),
+                    style_stderr!(bold(), "menu")
+                ),
+            )),
+            None => Ok(Self::Ignore),
+        }
+    }
+}
+
+/// A value the user is prompted for before an entry's run command is executed.
+///
+/// Every `{name}` in the run command is replaced by the value.
+#[derive(Debug, Clone)]
+pub struct Argument {
+    pub name: ImStr,
+    pub prompt: Option<ImStr>,
+    pub choices: Vec<ImStr>,
+}
+
+impl Argument {
+    const KEYS: &'static [&'static str] = &["name", "prompt", "choices"];
+
+    fn try_new(key: &str, arg: &Value) -> anyhow::Result<Self> {
+        match arg {
+            Value::String(name) => Ok(Self {
+                name: ImStr::from(name),
+                prompt: None,
+                choices: Vec::new(),
+            }),
+            Value::Table(table) => {
+                let name = table
+                    .get("name")
+                    .map(try_into_string(&format!("{key}.name")))
+                    .transpose()?
+                    .ok_or_else(|| {
+                        key_error(
+                            key,
+                            format!(
+                                "`{}` must have a value",
+                                style_stderr!(bold(), "{key}.name")
+                            ),
+                        )
+                    })?;
+
+                let prompt = table
+                    .get("prompt")
+                    .map(try_into_string(&format!("{key}.prompt")))
+                    .transpose()?;
+
+                let choices = table
+                    .get("choices")
+                    .map(try_into_array(&format!("{key}.choices")))
+                    .transpose()?
+                    .map(|choices| {
+                        choices
+                            .iter()
+                            .map(try_into_array_string(&format!("{key}.choices")))
+                            .collect::<Result<Vec<ImStr>, _>>()
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
+
+                Ok(Self {
+                    name,
+                    prompt,
+                    choices,
+                })
+            }
+            other => type_error(key, &["string", "table"], other.type_str()),
+        }
+    }
+
+    /// The text that is replaced by this argument's value.
+    pub fn placeholder(&self) -> String {
+        format!("{{{}}}", self.name)
+    }
+}
+
+/// A nested menu that is opened in place of the parent menu when its entry is selected.
+#[derive(Debug, Clone)]
+pub struct Submenu {
+    pub entries: Vec<Entry>,
+    pub prompt: Option<ImStr>,
+    pub back: Option<ImStr>,
+}
+
+impl Submenu {
+    const DEFAULT_BACK: ImStr = ImStr::new("..");
+
+    fn try_new(key: &str, table: &Map<String, Value>, menu: &Value) -> anyhow::Result<Self> {
+        let menu_key = format!("{key}.menu");
+        let entries = try_into_table(&menu_key)(menu)?
+            .iter()
+            .map(|(name, value)| Entry::try_new(&menu_key, ImStr::from(name), value))
+            .collect::<Result<Vec<Entry>, _>>()?;
+
+        let prompt = table
+            .get("prompt")
+            .map(try_into_string(&format!("{key}.prompt")))
+            .transpose()?;
+
+        let back = table
+            .get("back")
+            .map(|back| match back {
+                Value::Boolean(true) => Ok(Some(Self::DEFAULT_BACK)),
+                Value::Boolean(false) => Ok(None),
+                Value::String(back) => Ok(Some(ImStr::from(back))),
+                other => type_error(
+                    &format!("{key}.back"),
+                    &["boolean", "string"],
+                    other.type_str(),
+                ),
+            })
+            .transpose()?
+            .flatten();
+
+        Ok(Self {
+            entries,
+            prompt,
+            back,
+        })
+    }
+}
+
+/// A command run at launch that prints menu entries.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub name: ImStr,
+    pub run: Run,
+    pub each: Option<Run>,
+    pub format: Format,
+    pub group: i64,
+    pub cache: Option<u64>,
+    /// The options of each entry created from [`Self::each`].
+    pub options: RunOptions,
+}
+
+/// How the output of a [`Generator`] is read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Each line is the name of an entry.
+    #[default]
+    Lines,
+    /// Each line is a json object with a `name`, and the same keys as a `menu` table entry.
+    Json,
+}
+
+impl Generator {
+    const KEYS: &'static [&'static str] = &[
+        "run", "each", "format", "group", "cache", "terminal", "cwd", "env", "wait",
+    ];
+
+    fn try_new(name: ImStr, generator: &Value) -> anyhow::Result<Self> {
+        let key = format!("generator.{name}");
+        let table = try_into_table(&key)(generator)?;
+
+        let run = table
+            .get("run")
+            .map(|run| Run::try_new(&format!("{key}.run"), run))
+            .transpose()?
+            .ok_or_else(|| {
+                key_error(
+                    &key,
+                    format!("`{}` must have a value", style_stderr!(bold(), "{key}.run")),
+                )
+            })?;
+
+        let each = table
+            .get("each")
+            .map(|each| Run::try_new(&format!("{key}.each"), each))
+            .transpose()?;
+
+        let format = table
+            .get("format")
+            .map(try_into_string(&format!("{key}.format")))
+            .transpose()?
+            .map(|format| match format.as_str() {
+                "lines" => Ok(Format::Lines),
+                "json" => Ok(Format::Json),
+                other => Err(key_error(
+                    &format!("{key}.format"),
+                    format!(
+                        "`{}` must be `{}` or `{}`, but is `{}`",
+                        style_stderr!(bold(), "{key}.format"),
+                        style_stderr!(bold(), "lines"),
+                        style_stderr!(bold(), "json"),
+                        style_stderr!(bold(), "{other}")
+                    ),
+                )),
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let group = table
+            .get("group")
+            .map(try_into_integer(&format!("{key}.group")))
+            .transpose()?
+            .unwrap_or(0);
+
+        let cache = table
+            .get("cache")
+            .map(try_into_integer(&format!("{key}.cache")))
+            .transpose()?
+            .map(try_into_unsigned_integer(&format!("{key}.cache")))
+            .transpose()?;
+
+        let options = RunOptions::try_new(&key, table)?;
+
+        Ok(Self {
+            name,
+            run,
+            each,
+            format,
+            group,
+            cache,
+            options,
+        })
+    }
+
+    /// Convert the output of running [`Self::run`] into menu entries.
+    pub fn parse_output(&self, output: &str) -> anyhow::Result<Vec<Entry>> {
+        let key = format!("generator.{}", self.name);
+        let lines = output.lines().filter(|line| !line.trim().is_empty());
+
+        match self.format {
+            Format::Lines => Ok(lines
+                .map(|line| {
+                    let name = ImStr::from(line);
+                    match &self.each {
+                        Some(each) => Entry::Full {
+                            run: each.substitute(&[("{}", line)]),
+                            name,
+                            group: self.group,
+                            description: None,
+                            keywords: Rc::default(),
+                            args: Rc::default(),
+                            options: self.options.clone(),
+                        },
+                        None => Entry::Name(name),
+                    }
+                })
+                .collect()),
+            Format::Json => lines
+                .enumerate()
+                .map(|(i, line)| {
+                    let line_key = format!("{key}[{i}]");
+                    let value = serde_json::from_str::<Value>(line).context(format!(
+                        "line {} of `{}` output is not a valid json object",
+                        i + 1,
+                        style_stderr!(bold(), "{key}")
+                    ))?;
+                    let mut table = try_into_table(&line_key)(&value)?.clone();
+                    let name = table
+                        .remove("name")
+                        .map(|name| try_into_string(&format!("{line_key}.name"))(&name))
+                        .transpose()?
+                        .context(format!(
+                            "`{}` must have a value",
+                            style_stderr!(bold(), "{line_key}.name")
+                        ))?;
+                    table.entry("group").or_insert(Value::Integer(self.group));
+
+                    Entry::try_new(&key, name, &Value::Table(table))
+                })
+                .collect(),
         }
     }
 }
@@ -281,6 +1495,9 @@ impl ConfigItem for Shell {
     fn name() -> &'static str {
         "shell"
     }
+    fn keys() -> &'static [&'static str] {
+        &["shell", "piped"]
+    }
     fn merge(self, _: Self) -> Self {
         self
     }
@@ -326,25 +1543,322 @@ impl TryFrom<&Value> for Shell {
                     .transpose()?
                     .unwrap_or_default();
 
-                let piped = table
-                    .get("piped")
-                    .map(try_into_boolean("config.shell.piped"))
-                    .transpose()?
-                    .unwrap_or(false);
+                let piped = table
+                    .get("piped")
+                    .map(try_into_boolean("config.shell.piped"))
+                    .transpose()?
+                    .unwrap_or(false);
+
+                Ok(Self::Enabled { shell, piped })
+            }
+            other => type_error(
+                "config.shell",
+                &["boolean", "array", "table"],
+                other.type_str(),
+            ),
+        }
+    }
+}
+
+/// The terminal emulator that entries with `terminal = true` are run inside.
+#[derive(Debug, Default, Clone)]
+pub enum Terminal {
+    /// Use `$TERMINAL`, or else the first known terminal emulator found in `PATH`.
+    #[default]
+    Detect,
+    /// A command that runs the program given as its trailing arguments.
+    Command(Vec<ImStr>),
+}
+
+impl Terminal {
+    /// Terminal emulators to look for, in order of preference,
+    /// each with the arguments that make it run the program that follows.
+    const KNOWN: &'static [(&'static str, &'static [&'static str])] = &[
+        ("x-terminal-emulator", &["-e"]),
+        ("alacritty", &["-e"]),
+        ("kitty", &[]),
+        ("foot", &[]),
+        ("wezterm", &["start", "--"]),
+        ("gnome-terminal", &["--"]),
+        ("konsole", &["-e"]),
+        ("xfce4-terminal", &["-x"]),
+        ("st", &["-e"]),
+        ("urxvt", &["-e"]),
+        ("xterm", &["-e"]),
+    ];
+
+    /// The command to prepend to a program to run it inside a terminal emulator.
+    pub fn command(&self) -> anyhow::Result<Vec<ImStr>> {
+        if let Self::Command(command) = self {
+            return Ok(command.clone());
+        }
+
+        if let Some(terminal) = env::var("TERMINAL")
+            .ok()
+            .filter(|var| !var.trim().is_empty())
+        {
+            let mut command = terminal
+                .split_whitespace()
+                .map(ImStr::from)
+                .collect::<Vec<ImStr>>();
+            // `$TERMINAL` may already include the arguments needed to run a program.
+            if command.len() == 1 {
+                let name = Path::new(command[0].as_str())
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default();
+                let args = Self::KNOWN
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map_or(&["-e"][..], |(_, args)| args);
+                command.extend(args.iter().copied().map(ImStr::from));
+            }
+            return Ok(command);
+        }
+
+        let path = env::var_os("PATH").unwrap_or_default();
+        Self::KNOWN
+            .iter()
+            .find(|(name, _)| env::split_paths(&path).any(|dir| dir.join(name).is_executable()))
+            .map(|(name, args)| {
+                [*name]
+                    .iter()
+                    .chain(args.iter())
+                    .copied()
+                    .map(ImStr::from)
+                    .collect()
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "no terminal emulator was found; set `{}` or `{}`",
+                    style_stderr!(bold(), "config.terminal"),
+                    style_stderr!(bold(), "$TERMINAL")
+                )
+            })
+    }
+}
+
+impl ConfigItem for Terminal {
+    fn name() -> &'static str {
+        "terminal"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Terminal {
+    type Error = anyhow::Error;
+    fn try_from(terminal: &Value) -> anyhow::Result<Self> {
+        let command = try_into_array("config.terminal")(terminal)?
+            .iter()
+            .map(try_into_array_string("config.terminal"))
+            .collect::<Result<Vec<ImStr>, _>>()?;
+
+        if command.is_empty() {
+            return Err(key_error(
+                "config.terminal",
+                format!(
+                    "`{}` must not be empty",
+                    style_stderr!(bold(), "config.terminal")
+                ),
+            ));
+        }
+
+        Ok(Self::Command(command))
+    }
+}
+
+/// Environment variables set for every command, unless overridden by an entry's `env`.
+#[derive(Debug, Default, Clone)]
+pub struct Env(pub Vec<(ImStr, ImStr)>);
+
+impl ConfigItem for Env {
+    fn name() -> &'static str {
+        "env"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, default: Self) -> Self {
+        let mut env = self.0;
+        let inherited = default
+            .0
+            .into_iter()
+            .filter(|(name, _)| !env.iter().any(|(set, _)| set == name))
+            .collect::<Vec<(ImStr, ImStr)>>();
+        env.extend(inherited);
+        Self(env)
+    }
+}
+
+impl TryFrom<&Value> for Env {
+    type Error = anyhow::Error;
+    fn try_from(env: &Value) -> anyhow::Result<Self> {
+        try_into_env("config.env", env).map(Self)
+    }
+}
+
+/// How launched commands are separated from dmm.
+#[derive(Debug, Clone)]
+pub enum Detach {
+    /// Commands share dmm's stdio, session, and process group.
+    Disabled,
+    /// Commands run as orphans in their own session,
+    /// with their output appended to `log`, or discarded if there is no log.
+    Enabled { log: Option<ImStr> },
+}
+
+impl Default for Detach {
+    fn default() -> Self {
+        Self::Enabled { log: None }
+    }
+}
+
+impl ConfigItem for Detach {
+    fn name() -> &'static str {
+        "detach"
+    }
+    fn keys() -> &'static [&'static str] {
+        &["detach", "log"]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Detach {
+    type Error = anyhow::Error;
+    fn try_from(detach: &Value) -> anyhow::Result<Self> {
+        match detach {
+            Value::Boolean(false) => Ok(Self::Disabled),
+            Value::Boolean(true) => Ok(Self::default()),
+            Value::Table(detach) => {
+                let enabled = detach
+                    .get("detach")
+                    .map(try_into_boolean("config.detach.detach"))
+                    .transpose()?
+                    .unwrap_or(true);
+
+                let log = detach
+                    .get("log")
+                    .map(try_into_string("config.detach.log"))
+                    .transpose()?;
+
+                if enabled {
+                    Ok(Self::Enabled { log })
+                } else {
+                    Ok(Self::Disabled)
+                }
+            }
+            other => type_error("config.detach", &["boolean", "table"], other.type_str()),
+        }
+    }
+}
+
+/// A command that's run to notify the user when a command with `wait = true` fails.
+#[derive(Debug, Default, Clone)]
+pub enum Notify {
+    #[default]
+    Disabled,
+    /// The command is given a summary and the cause of the failure as its last two arguments.
+    Enabled(Vec<ImStr>),
+}
+
+impl ConfigItem for Notify {
+    fn name() -> &'static str {
+        "notify"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Notify {
+    type Error = anyhow::Error;
+    fn try_from(notify: &Value) -> anyhow::Result<Self> {
+        match notify {
+            Value::Boolean(false) => Ok(Self::Disabled),
+            Value::Boolean(true) => Ok(Self::Enabled(vec![ImStr::new("notify-send")])),
+            Value::Array(notify) => Ok(Self::Enabled(
+                notify
+                    .iter()
+                    .map(try_into_array_string("config.notify"))
+                    .collect::<Result<Vec<ImStr>, _>>()?,
+            )),
+            other => type_error("config.notify", &["boolean", "array"], other.type_str()),
+        }
+    }
+}
 
-                Ok(Self::Enabled { shell, piped })
-            }
-            other => type_error(
-                "config.shell",
-                &["boolean", "array", "table"],
-                other.type_str(),
-            ),
+/// The command that copies the text written to its stdin to the clipboard.
+#[derive(Debug, Default, Clone)]
+pub enum Clipboard {
+    /// Use `wl-copy` on wayland, or else `xclip`.
+    #[default]
+    Detect,
+    Command(Vec<ImStr>),
+}
+
+impl Clipboard {
+    pub fn command(&self) -> Vec<ImStr> {
+        let detected: &[&str] = match self {
+            Self::Command(command) => return command.clone(),
+            Self::Detect if is_wayland() => &["wl-copy"],
+            Self::Detect => &["xclip", "-selection", "clipboard"],
+        };
+        detected.iter().copied().map(ImStr::from).collect()
+    }
+}
+
+impl ConfigItem for Clipboard {
+    fn name() -> &'static str {
+        "clipboard"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Clipboard {
+    type Error = anyhow::Error;
+    fn try_from(clipboard: &Value) -> anyhow::Result<Self> {
+        let command = try_into_array("config.clipboard")(clipboard)?
+            .iter()
+            .map(try_into_array_string("config.clipboard"))
+            .collect::<Result<Vec<ImStr>, _>>()?;
+
+        if command.is_empty() {
+            return Err(key_error(
+                "config.clipboard",
+                format!(
+                    "`{}` must not be empty",
+                    style_stderr!(bold(), "config.clipboard")
+                ),
+            ));
         }
+
+        Ok(Self::Command(command))
     }
 }
 
-#[derive(Debug, Clone)]
+/// Whether dmm is running in a wayland session.
+pub fn is_wayland() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty())
+}
+
+#[derive(Debug, Default, Clone)]
 pub enum Custom {
+    #[default]
     Disabled,
     Enabled,
 }
@@ -353,17 +1867,14 @@ impl ConfigItem for Custom {
     fn name() -> &'static str {
         "custom"
     }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
     fn merge(self, _: Self) -> Self {
         self
     }
 }
 
-impl Default for Custom {
-    fn default() -> Self {
-        Self::Disabled
-    }
-}
-
 impl TryFrom<&Value> for Custom {
     type Error = anyhow::Error;
     fn try_from(custom: &Value) -> anyhow::Result<Self> {
@@ -375,8 +1886,9 @@ impl TryFrom<&Value> for Custom {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub enum Numbered {
+    #[default]
     Disabled,
     Enabled(Separator),
 }
@@ -401,17 +1913,14 @@ impl ConfigItem for Numbered {
     fn name() -> &'static str {
         "numbered"
     }
+    fn keys() -> &'static [&'static str] {
+        &["numbered", "separator"]
+    }
     fn merge(self, _: Self) -> Self {
         self
     }
 }
 
-impl Default for Numbered {
-    fn default() -> Self {
-        Self::Disabled
-    }
-}
-
 impl TryFrom<&Value> for Numbered {
     type Error = anyhow::Error;
     fn try_from(numbered: &Value) -> anyhow::Result<Self> {
@@ -470,14 +1979,16 @@ impl TryFrom<&Value> for Separator {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub enum BinPath {
+    #[default]
     Disabled,
     Enabled {
         path: Vec<ImStr>,
         env: bool,
         replace: bool,
         recursive: bool,
+        cache: bool,
         group: i64,
     },
 }
@@ -486,17 +1997,14 @@ impl ConfigItem for BinPath {
     fn name() -> &'static str {
         "path"
     }
+    fn keys() -> &'static [&'static str] {
+        &["path", "env", "replace", "recursive", "cache", "group"]
+    }
     fn merge(self, _: Self) -> Self {
         self
     }
 }
 
-impl Default for BinPath {
-    fn default() -> Self {
-        Self::Disabled
-    }
-}
-
 impl TryFrom<&Value> for BinPath {
     type Error = anyhow::Error;
     fn try_from(path: &Value) -> anyhow::Result<Self> {
@@ -507,6 +2015,7 @@ impl TryFrom<&Value> for BinPath {
                 env: true,
                 replace: false,
                 recursive: false,
+                cache: true,
                 group: 0,
             }),
             Value::Array(array) => {
@@ -520,6 +2029,7 @@ impl TryFrom<&Value> for BinPath {
                     env: false,
                     replace: false,
                     recursive: false,
+                    cache: true,
                     group: 0,
                 })
             }
@@ -555,6 +2065,12 @@ impl TryFrom<&Value> for BinPath {
                     .transpose()?
                     .unwrap_or(false);
 
+                let cache = table
+                    .get("cache")
+                    .map(try_into_boolean("config.path.cache"))
+                    .transpose()?
+                    .unwrap_or(true);
+
                 let group = table
                     .get("group")
                     .map(try_into_integer("config.path.group"))
@@ -566,68 +2082,573 @@ impl TryFrom<&Value> for BinPath {
                     env,
                     replace,
                     recursive,
+                    cache,
                     group,
                 })
             }
             other => type_error(
-                "config.numbered.separator",
+                "config.path",
+                &["boolean", "array", "table"],
+                other.type_str(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub enum Desktop {
+    #[default]
+    Disabled,
+    Enabled {
+        path: Vec<ImStr>,
+        xdg: bool,
+        replace: bool,
+        group: i64,
+    },
+}
+
+impl ConfigItem for Desktop {
+    fn name() -> &'static str {
+        "desktop"
+    }
+    fn keys() -> &'static [&'static str] {
+        &["path", "xdg", "replace", "group"]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Desktop {
+    type Error = anyhow::Error;
+    fn try_from(desktop: &Value) -> anyhow::Result<Self> {
+        match desktop {
+            Value::Boolean(false) => Ok(Self::Disabled),
+            Value::Boolean(true) => Ok(Self::Enabled {
+                path: Vec::new(),
+                xdg: true,
+                replace: false,
+                group: 0,
+            }),
+            Value::Array(array) => {
+                let path = array
+                    .iter()
+                    .map(try_into_array_string("config.desktop"))
+                    .collect::<Result<Vec<ImStr>, _>>()?;
+
+                Ok(Self::Enabled {
+                    path,
+                    xdg: false,
+                    replace: false,
+                    group: 0,
+                })
+            }
+            Value::Table(table) => {
+                let path = table
+                    .get("path")
+                    .map(try_into_array("config.desktop.path"))
+                    .transpose()?
+                    .map(|value| {
+                        value
+                            .iter()
+                            .map(try_into_array_string("config.desktop.path"))
+                            .collect::<Result<Vec<ImStr>, _>>()
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
+
+                let xdg = table
+                    .get("xdg")
+                    .map(try_into_boolean("config.desktop.xdg"))
+                    .transpose()?
+                    .unwrap_or(false);
+
+                let replace = table
+                    .get("replace")
+                    .map(try_into_boolean("config.desktop.replace"))
+                    .transpose()?
+                    .unwrap_or(false);
+
+                let group = table
+                    .get("group")
+                    .map(try_into_integer("config.desktop.group"))
+                    .transpose()?
+                    .unwrap_or(0);
+
+                Ok(Self::Enabled {
+                    path,
+                    xdg,
+                    replace,
+                    group,
+                })
+            }
+            other => type_error(
+                "config.desktop",
                 &["boolean", "array", "table"],
                 other.type_str(),
             ),
-        }
-    }
-}
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    #[default]
+    Name,
+    Frecency,
+}
+
+impl ConfigItem for Sort {
+    fn name() -> &'static str {
+        "sort"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Sort {
+    type Error = anyhow::Error;
+    fn try_from(sort: &Value) -> anyhow::Result<Self> {
+        match try_into_string("config.sort")(sort)?.as_str() {
+            "name" => Ok(Self::Name),
+            "frecency" => Ok(Self::Frecency),
+            other => Err(key_error(
+                "config.sort",
+                format!(
+                    "`{}` must be `{}` or `{}`, but is `{}`",
+                    style_stderr!(bold(), "config.sort"),
+                    style_stderr!(bold(), "name"),
+                    style_stderr!(bold(), "frecency"),
+                    style_stderr!(bold(), "{other}")
+                ),
+            )),
+        }
+    }
+}
+
+/// How entries are displayed in the launcher.
+#[derive(Debug, Default, Clone)]
+pub struct EntryDisplay {
+    /// Whether entry descriptions are matched while searching; unset unless a config sets it.
+    pub description: Option<bool>,
+}
+
+impl EntryDisplay {
+    /// Whether entry descriptions are matched while searching; the default is true.
+    pub fn search_description(&self) -> bool {
+        self.description.unwrap_or(true)
+    }
+}
+
+impl ConfigItem for EntryDisplay {
+    fn name() -> &'static str {
+        "display"
+    }
+    fn keys() -> &'static [&'static str] {
+        &["description"]
+    }
+    fn merge(self, default: Self) -> Self {
+        Self {
+            description: self.description.or(default.description),
+        }
+    }
+}
+
+impl TryFrom<&Value> for EntryDisplay {
+    type Error = anyhow::Error;
+    fn try_from(display: &Value) -> anyhow::Result<Self> {
+        let display = try_into_table("config.display")(display)?;
+
+        Ok(Self {
+            description: display
+                .get("description")
+                .map(try_into_boolean("config.display.description"))
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    #[default]
+    Dmenu,
+    Rofi,
+    Wofi,
+    Fuzzel,
+    Bemenu,
+    Tofi,
+    Fzf,
+}
+
+impl Launcher {
+    const NAMES: [&'static str; 7] = ["dmenu", "rofi", "wofi", "fuzzel", "bemenu", "tofi", "fzf"];
+
+    pub const fn command(self) -> &'static str {
+        match self {
+            Self::Dmenu => "dmenu",
+            Self::Rofi => "rofi",
+            Self::Wofi => "wofi",
+            Self::Fuzzel => "fuzzel",
+            Self::Bemenu => "bemenu",
+            Self::Tofi => "tofi",
+            Self::Fzf => "fzf",
+        }
+    }
+}
+
+impl ConfigItem for Launcher {
+    fn name() -> &'static str {
+        "launcher"
+    }
+    fn keys() -> &'static [&'static str] {
+        &[]
+    }
+    fn merge(self, _: Self) -> Self {
+        self
+    }
+}
+
+impl TryFrom<&Value> for Launcher {
+    type Error = anyhow::Error;
+    fn try_from(launcher: &Value) -> anyhow::Result<Self> {
+        match try_into_string("config.launcher")(launcher)?.as_str() {
+            "dmenu" => Ok(Self::Dmenu),
+            "rofi" => Ok(Self::Rofi),
+            "wofi" => Ok(Self::Wofi),
+            "fuzzel" => Ok(Self::Fuzzel),
+            "bemenu" => Ok(Self::Bemenu),
+            "tofi" => Ok(Self::Tofi),
+            "fzf" => Ok(Self::Fzf),
+            other => Err(key_error(
+                "config.launcher",
+                format!(
+                    "`{}` must be one of {}, but is `{}`",
+                    style_stderr!(bold(), "config.launcher"),
+                    Self::NAMES
+                        .iter()
+                        .map(|name| format!("`{}`", style_stderr!(bold(), "{name}")))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    style_stderr!(bold(), "{other}")
+                ),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Dmenu {
+    /// Strings are unset unless a config sets them, and stay cleared if a config clears them.
+    pub prompt: Option<Clearable>,
+    pub font: Option<Clearable>,
+    pub background: Option<Clearable>,
+    pub foreground: Option<Clearable>,
+    pub selected_background: Option<Clearable>,
+    pub selected_foreground: Option<Clearable>,
+    pub lines: Option<u64>,
+    /// Booleans are unset unless a config sets them, so a config can override another with `false`.
+    pub bottom: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    pub fast: Option<bool>,
+    pub monitor: Option<u64>,
+    pub window_id: Option<Clearable>,
+}
+
+/// A string option that a config may set to `false`, to unset a value inherited from another config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Clearable {
+    Set(ImStr),
+    Cleared,
+}
+
+/// The value of a string option, unless it's unset or cleared.
+pub fn set_value(option: &Option<Clearable>) -> Option<&str> {
+    match option {
+        Some(Clearable::Set(value)) => Some(value),
+        Some(Clearable::Cleared) | None => None,
+    }
+}
+
+impl Dmenu {
+    /// Translate the shared `config.dmenu` options into flags understood by `launcher`.
+    ///
+    /// Options a launcher has no equivalent for are silently ignored.
+    pub fn args(&self, launcher: Launcher) -> Vec<Cow<'_, str>> {
+        match launcher {
+            Launcher::Dmenu => self.dmenu_args(),
+            Launcher::Rofi => self.rofi_args(),
+            Launcher::Wofi => self.wofi_args(),
+            Launcher::Fuzzel => self.fuzzel_args(),
+            Launcher::Bemenu => self.bemenu_args(),
+            Launcher::Tofi => self.tofi_args(),
+            Launcher::Fzf => self.fzf_args(),
+        }
+    }
+
+    fn dmenu_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = Vec::with_capacity(12);
+
+        let options = [
+            ("-p", set_value(&self.prompt).map(Cow::from)),
+            ("-fn", set_value(&self.font).map(Cow::from)),
+            ("-nb", set_value(&self.background).map(Cow::from)),
+            ("-nf", set_value(&self.foreground).map(Cow::from)),
+            ("-sb", set_value(&self.selected_background).map(Cow::from)),
+            ("-sf", set_value(&self.selected_foreground).map(Cow::from)),
+            ("-w", set_value(&self.window_id).map(Cow::from)),
+            ("-l", self.lines.map(|int| Cow::from(int.to_string()))),
+            ("-m", self.monitor.map(|int| Cow::from(int.to_string()))),
+        ];
+
+        self.bottom
+            .unwrap_or(false)
+            .then(|| args.push(Cow::from("-b")));
+        (!self.case_sensitive.unwrap_or(false)).then(|| args.push(Cow::from("-i")));
+        self.fast
+            .unwrap_or(false)
+            .then(|| args.push(Cow::from("-f")));
+
+        push_options(&mut args, options);
+        args
+    }
+
+    fn rofi_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = vec![Cow::from("-dmenu")];
+
+        let mut theme = String::new();
+        if set_value(&self.background).is_some() || set_value(&self.foreground).is_some() {
+            theme.push_str("* {");
+            if let Some(background) = set_value(&self.background) {
+                write!(theme, " background-color: {background};").unwrap();
+            }
+            if let Some(foreground) = set_value(&self.foreground) {
+                write!(theme, " text-color: {foreground};").unwrap();
+            }
+            theme.push_str(" } ");
+        }
+        if let Some(background) = set_value(&self.selected_background) {
+            write!(
+                theme,
+                "element selected {{ background-color: {background}; }} "
+            )
+            .unwrap();
+        }
+        if let Some(foreground) = set_value(&self.selected_foreground) {
+            write!(
+                theme,
+                "element-text selected {{ text-color: {foreground}; }} "
+            )
+            .unwrap();
+        }
+        if self.bottom.unwrap_or(false) {
+            theme.push_str("window { location: south; anchor: south; } ");
+        }
+
+        let options = [
+            ("-p", set_value(&self.prompt).map(Cow::from)),
+            ("-font", set_value(&self.font).map(Cow::from)),
+            ("-w", set_value(&self.window_id).map(Cow::from)),
+            ("-l", self.lines.map(|int| Cow::from(int.to_string()))),
+            ("-m", self.monitor.map(|int| Cow::from(int.to_string()))),
+            (
+                "-theme-str",
+                (!theme.is_empty()).then(|| Cow::from(theme.trim_end().to_owned())),
+            ),
+        ];
+
+        (!self.case_sensitive.unwrap_or(false)).then(|| args.push(Cow::from("-i")));
+
+        push_options(&mut args, options);
+        args
+    }
+
+    fn wofi_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = vec![Cow::from("--dmenu")];
+
+        let options = [
+            ("--prompt", set_value(&self.prompt).map(Cow::from)),
+            ("--lines", self.lines.map(|int| Cow::from(int.to_string()))),
+        ];
+
+        self.bottom
+            .unwrap_or(false)
+            .then(|| args.extend([Cow::from("--location"), Cow::from("bottom")]));
+        (!self.case_sensitive.unwrap_or(false)).then(|| args.push(Cow::from("--insensitive")));
+
+        push_options(&mut args, options);
+        args
+    }
+
+    fn fuzzel_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = vec![Cow::from("--dmenu")];
+
+        let options = [
+            ("--prompt", set_value(&self.prompt).map(Cow::from)),
+            ("--font", set_value(&self.font).map(Cow::from)),
+            ("--background", set_value(&self.background).map(hex_rgba)),
+            ("--text-color", set_value(&self.foreground).map(hex_rgba)),
+            (
+                "--selection-color",
+                set_value(&self.selected_background).map(hex_rgba),
+            ),
+            (
+                "--selection-text-color",
+                set_value(&self.selected_foreground).map(hex_rgba),
+            ),
+            ("--lines", self.lines.map(|int| Cow::from(int.to_string()))),
+        ];
 
-#[derive(Debug, Default, Clone)]
-pub struct Dmenu {
-    pub prompt: Option<ImStr>,
-    pub font: Option<ImStr>,
-    pub background: Option<ImStr>,
-    pub foreground: Option<ImStr>,
-    pub selected_background: Option<ImStr>,
-    pub selected_foreground: Option<ImStr>,
-    pub lines: Option<u64>,
-    pub bottom: bool,
-    pub case_sensitive: bool,
-    pub fast: bool,
-    pub monitor: Option<u64>,
-    pub window_id: Option<ImStr>,
-}
+        self.bottom
+            .unwrap_or(false)
+            .then(|| args.extend([Cow::from("--anchor"), Cow::from("bottom")]));
 
-impl Dmenu {
-    pub fn args(&self) -> Vec<Cow<'_, str>> {
+        push_options(&mut args, options);
+        args
+    }
+
+    fn bemenu_args(&self) -> Vec<Cow<'_, str>> {
         let mut args = Vec::with_capacity(12);
 
         let options = [
-            ("-p", self.prompt.as_deref().map(Cow::from)),
-            ("-fn", self.font.as_deref().map(Cow::from)),
-            ("-nb", self.background.as_deref().map(Cow::from)),
-            ("-nf", self.foreground.as_deref().map(Cow::from)),
-            ("-sb", self.selected_background.as_deref().map(Cow::from)),
-            ("-sf", self.selected_foreground.as_deref().map(Cow::from)),
-            ("-w", self.window_id.as_deref().map(Cow::from)),
+            ("-p", set_value(&self.prompt).map(Cow::from)),
+            ("--fn", set_value(&self.font).map(Cow::from)),
+            ("--nb", set_value(&self.background).map(Cow::from)),
+            ("--nf", set_value(&self.foreground).map(Cow::from)),
+            ("--hb", set_value(&self.selected_background).map(Cow::from)),
+            ("--hf", set_value(&self.selected_foreground).map(Cow::from)),
             ("-l", self.lines.map(|int| Cow::from(int.to_string()))),
             ("-m", self.monitor.map(|int| Cow::from(int.to_string()))),
         ];
 
-        self.bottom.then(|| args.push(Cow::from("-b")));
-        (!self.case_sensitive).then(|| args.push(Cow::from("-i")));
-        self.fast.then(|| args.push(Cow::from("-f")));
+        self.bottom
+            .unwrap_or(false)
+            .then(|| args.push(Cow::from("-b")));
+        (!self.case_sensitive.unwrap_or(false)).then(|| args.push(Cow::from("-i")));
 
-        for (flag, option) in options {
-            if let Some(option) = option {
-                args.extend([Cow::from(flag), option]);
-            }
-        }
+        push_options(&mut args, options);
+        args
+    }
+
+    fn tofi_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = Vec::with_capacity(12);
+
+        let options = [
+            ("--prompt-text", set_value(&self.prompt).map(Cow::from)),
+            ("--font", set_value(&self.font).map(Cow::from)),
+            (
+                "--background-color",
+                set_value(&self.background).map(Cow::from),
+            ),
+            ("--text-color", set_value(&self.foreground).map(Cow::from)),
+            (
+                "--selection-background",
+                set_value(&self.selected_background).map(Cow::from),
+            ),
+            (
+                "--selection-color",
+                set_value(&self.selected_foreground).map(Cow::from),
+            ),
+            (
+                "--num-results",
+                self.lines.map(|int| Cow::from(int.to_string())),
+            ),
+        ];
+
+        self.bottom
+            .unwrap_or(false)
+            .then(|| args.extend([Cow::from("--anchor"), Cow::from("bottom")]));
+
+        push_options(&mut args, options);
+        args
+    }
 
+    fn fzf_args(&self) -> Vec<Cow<'_, str>> {
+        let mut args = Vec::with_capacity(6);
+
+        let colors = [
+            ("bg", set_value(&self.background)),
+            ("fg", set_value(&self.foreground)),
+            ("bg+", set_value(&self.selected_background)),
+            ("fg+", set_value(&self.selected_foreground)),
+        ]
+        .into_iter()
+        .filter_map(|(name, color)| color.map(|color| format!("{name}:{color}")))
+        .collect::<Vec<String>>();
+
+        let options = [
+            ("--prompt", set_value(&self.prompt).map(Cow::from)),
+            (
+                "--color",
+                (!colors.is_empty()).then(|| Cow::from(colors.join(","))),
+            ),
+            (
+                "--height",
+                self.lines.map(|int| Cow::from((int + 1).to_string())),
+            ),
+        ];
+
+        args.push(Cow::from(if self.case_sensitive.unwrap_or(false) {
+            "+i"
+        } else {
+            "-i"
+        }));
+        (!self.bottom.unwrap_or(false)).then(|| args.push(Cow::from("--reverse")));
+
+        push_options(&mut args, options);
         args
     }
 }
 
+fn push_options<'a, const N: usize>(
+    args: &mut Vec<Cow<'a, str>>,
+    options: [(&'static str, Option<Cow<'a, str>>); N],
+) {
+    for (flag, option) in options {
+        if let Some(option) = option {
+            args.extend([Cow::from(flag), option]);
+        }
+    }
+}
+
+/// Convert a `#rrggbb` or `#rgb` color into the `rrggbbaa` format used by some launchers.
+fn hex_rgba(color: &str) -> Cow<'_, str> {
+    let hex = color.trim_start_matches('#');
+    match hex.len() {
+        3 => Cow::from(
+            hex.chars()
+                .flat_map(|c| [c, c])
+                .chain(['f', 'f'])
+                .collect::<String>(),
+        ),
+        6 => Cow::from(format!("{hex}ff")),
+        _ => Cow::from(hex),
+    }
+}
+
 impl ConfigItem for Dmenu {
     fn name() -> &'static str {
         "dmenu"
     }
+    fn keys() -> &'static [&'static str] {
+        &[
+            "prompt",
+            "font",
+            "background",
+            "foreground",
+            "selected-background",
+            "selected-foreground",
+            "lines",
+            "bottom",
+            "case-sensitive",
+            "fast",
+            "monitor",
+            "window-id",
+        ]
+    }
     fn merge(self, default: Self) -> Self {
         Self {
             prompt: self.prompt.or(default.prompt),
@@ -637,9 +2658,9 @@ impl ConfigItem for Dmenu {
             selected_background: self.selected_background.or(default.selected_background),
             selected_foreground: self.selected_foreground.or(default.selected_foreground),
             lines: self.lines.or(default.lines),
-            bottom: self.bottom || default.bottom,
-            case_sensitive: self.case_sensitive || default.case_sensitive,
-            fast: self.fast || default.fast,
+            bottom: self.bottom.or(default.bottom),
+            case_sensitive: self.case_sensitive.or(default.case_sensitive),
+            fast: self.fast.or(default.fast),
             monitor: self.monitor.or(default.monitor),
             window_id: self.window_id.or(default.window_id),
         }
@@ -654,27 +2675,31 @@ impl TryFrom<&Value> for Dmenu {
         Ok(Self {
             prompt: dmenu
                 .get("prompt")
-                .map(try_into_string("config.dmenu.prompt"))
+                .map(try_into_clearable_string("config.dmenu.prompt"))
                 .transpose()?,
             font: dmenu
                 .get("font")
-                .map(try_into_string("config.dmenu.font"))
+                .map(try_into_clearable_string("config.dmenu.font"))
                 .transpose()?,
             background: dmenu
                 .get("background")
-                .map(try_into_string("config.dmenu.background"))
+                .map(try_into_clearable_string("config.dmenu.background"))
                 .transpose()?,
             foreground: dmenu
                 .get("foreground")
-                .map(try_into_string("config.dmenu.foreground"))
+                .map(try_into_clearable_string("config.dmenu.foreground"))
                 .transpose()?,
             selected_background: dmenu
                 .get("selected-background")
-                .map(try_into_string("config.dmenu.selected-background"))
+                .map(try_into_clearable_string(
+                    "config.dmenu.selected-background",
+                ))
                 .transpose()?,
             selected_foreground: dmenu
                 .get("selected-foreground")
-                .map(try_into_string("config.dmenu.selected-foreground"))
+                .map(try_into_clearable_string(
+                    "config.dmenu.selected-foreground",
+                ))
                 .transpose()?,
             lines: dmenu
                 .get("lines")
@@ -685,18 +2710,15 @@ impl TryFrom<&Value> for Dmenu {
             bottom: dmenu
                 .get("bottom")
                 .map(try_into_boolean("config.dmenu.bottom"))
-                .transpose()?
-                .unwrap_or(false),
+                .transpose()?,
             case_sensitive: dmenu
                 .get("case-sensitive")
                 .map(try_into_boolean("config.dmenu.case-sensitive"))
-                .transpose()?
-                .unwrap_or(false),
+                .transpose()?,
             fast: dmenu
                 .get("fast")
                 .map(try_into_boolean("config.dmenu.fast"))
-                .transpose()?
-                .unwrap_or(false),
+                .transpose()?,
             monitor: dmenu
                 .get("monitor")
                 .map(try_into_integer("config.dmenu.monitor"))
@@ -705,7 +2727,7 @@ impl TryFrom<&Value> for Dmenu {
                 .transpose()?,
             window_id: dmenu
                 .get("window-id")
-                .map(try_into_string("config.dmenu.window-id"))
+                .map(try_into_clearable_string("config.dmenu.window-id"))
                 .transpose()?,
         })
     }
@@ -717,104 +2739,645 @@ pub struct Config {
     pub dirs: ProjectDirs,
     pub base_dirs: BaseDirs,
     pub entries: Vec<Entry>,
+    pub generators: Vec<Generator>,
     pub shell: Shell,
+    pub terminal: Terminal,
+    pub env: Env,
+    pub detach: Detach,
+    pub notify: Notify,
+    pub clipboard: Clipboard,
     pub custom: Custom,
     pub numbered: Numbered,
     pub path: BinPath,
+    pub desktop: Desktop,
+    pub sort: Sort,
+    pub display: EntryDisplay,
+    pub launcher: Launcher,
     pub dmenu: Dmenu,
+    pub unknown_keys: Vec<UnknownKey>,
+    pub origins: Vec<Origin>,
 }
 
 impl Config {
+    /// Load the pattern at `path` in place of any patterns given as arguments,
+    /// keeping every other config.
+    pub fn with_pattern(self, path: &Path) -> anyhow::Result<Self> {
+        build(
+            vec![read_pattern(path)?],
+            self.args,
+            self.dirs,
+            self.base_dirs,
+        )
+    }
+
+    /// The directory to pick a pattern from, if `--pick` was given.
+    pub fn pick_dir(&self) -> Option<PathBuf> {
+        if !self.args.contains_id("pick") {
+            return None;
+        }
+        Some(
+            self.args
+                .get_one::<PathBuf>("pick")
+                .cloned()
+                .unwrap_or_else(|| self.dirs.config_dir().join("patterns")),
+        )
+    }
+
+    /// Combine `layers`, which must be in order of decreasing precedence.
     pub fn try_new(
-        config: &Value,
-        home_config: Option<&Value>,
+        layers: &[Layer],
         args: ArgMatches,
         dirs: ProjectDirs,
         base_dirs: BaseDirs,
     ) -> anyhow::Result<Self> {
-        let config_path = dirs.config_dir().join("config.toml");
-        Ok(Self {
-            entries: try_get_entries(config, home_config, &config_path)?,
-            shell: try_get_config::<Shell>(config, home_config, &config_path)?,
-            custom: try_get_config::<Custom>(config, home_config, &config_path)?,
-            numbered: try_get_config::<Numbered>(config, home_config, &config_path)?,
-            path: try_get_config::<BinPath>(config, home_config, &config_path)?,
-            dmenu: try_get_config::<Dmenu>(config, home_config, &config_path)?,
-            args,
-            dirs,
-            base_dirs,
-        })
+        let unknown_keys = layers
+            .iter()
+            // A profile's keys are checked as part of the config that defines it.
+            .filter(|layer| !matches!(layer.source, ConfigSource::Profile(..)))
+            .flat_map(|layer| find_unknown_keys(&layer.config, &layer.source))
+            .collect();
+
+        let parsed = (|| -> anyhow::Result<Self> {
+            check_metadata(layers)?;
+            Ok(Self {
+                entries: try_get_entries(layers)?,
+                generators: try_get_generators(layers)?,
+                shell: try_get_config::<Shell>(layers)?,
+                terminal: try_get_config::<Terminal>(layers)?,
+                env: try_get_config::<Env>(layers)?,
+                detach: try_get_config::<Detach>(layers)?,
+                notify: try_get_config::<Notify>(layers)?,
+                clipboard: try_get_config::<Clipboard>(layers)?,
+                custom: try_get_config::<Custom>(layers)?,
+                numbered: try_get_config::<Numbered>(layers)?,
+                path: try_get_config::<BinPath>(layers)?,
+                desktop: try_get_config::<Desktop>(layers)?,
+                sort: try_get_config::<Sort>(layers)?,
+                display: try_get_config::<EntryDisplay>(layers)?,
+                launcher: try_get_config::<Launcher>(layers)?,
+                dmenu: try_get_config::<Dmenu>(layers)?,
+                unknown_keys: Vec::new(),
+                origins: find_origins(layers),
+                args,
+                dirs,
+                base_dirs,
+            })
+        })();
+
+        match parsed {
+            Ok(parsed) => Ok(Self {
+                unknown_keys,
+                ..parsed
+            }),
+            // An unknown key is often the cause of another problem, such as a missing value.
+            Err(err) if !unknown_keys.is_empty() => Err(err.context(UnknownKeys(unknown_keys))),
+            Err(err) => Err(err),
+        }
     }
 }
 
-fn try_get_entries(
-    config: &Value,
-    home_config: Option<&Value>,
-    config_path: &Path,
-) -> anyhow::Result<Vec<Entry>> {
-    let mut menu = config
-        .get("menu")
-        .map(try_into_table("menu"))
-        .transpose()?
-        .into_iter()
-        .flatten()
-        .map(|(name, value)| Entry::try_new(ImStr::from(name), value))
-        .collect::<Result<Vec<Entry>, _>>()
-        .context(target_config_error())?;
-
-    let home_menu = home_config
-        .and_then(|config| config.get("menu"))
-        .map(try_into_table("menu"))
-        .transpose()?
+/// Check the `title` and `description` that describe a pattern for `--pick`.
+fn check_metadata(layers: &[Layer]) -> anyhow::Result<()> {
+    for layer in layers {
+        for key in ["title", "description"] {
+            layer
+                .config
+                .get(key)
+                .map(try_into_string(key))
+                .transpose()
+                .context(SourceProblem(layer.source.clone()))?;
+        }
+    }
+    Ok(())
+}
+
+fn try_get_entries(layers: &[Layer]) -> anyhow::Result<Vec<Entry>> {
+    let mut menu = Vec::new();
+    let mut entry_names = HashSet::default();
+
+    for layer in layers {
+        let mut entries = layer
+            .config
+            .get("menu")
+            .map(try_into_table("menu"))
+            .transpose()
+            .context(SourceProblem(layer.source.clone()))?
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| Entry::try_new("menu", ImStr::from(name), value))
+            .collect::<Result<Vec<Entry>, _>>()
+            .context(SourceProblem(layer.source.clone()))?;
+
+        // Names are unique within a layer, so only entries from earlier layers are removed.
+        entries.retain(|entry| entry_names.insert(entry.name()));
+        menu.extend(entries);
+    }
+
+    Ok(menu)
+}
+
+fn try_get_generators(layers: &[Layer]) -> anyhow::Result<Vec<Generator>> {
+    let mut generators = Vec::new();
+    let mut names = HashSet::default();
+
+    for layer in layers {
+        let mut layer_generators = layer
+            .config
+            .get("generator")
+            .map(try_into_table("generator"))
+            .transpose()
+            .context(SourceProblem(layer.source.clone()))?
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| Generator::try_new(ImStr::from(name), value))
+            .collect::<Result<Vec<Generator>, _>>()
+            .context(SourceProblem(layer.source.clone()))?;
+
+        layer_generators.retain(|generator| names.insert(generator.name.clone()));
+        generators.extend(layer_generators);
+    }
+
+    Ok(generators)
+}
+
+fn try_get_config<T: ConfigItem>(layers: &[Layer]) -> anyhow::Result<T> {
+    let items = layers
+        .iter()
+        .map(|layer| {
+            layer
+                .config
+                .get("config")
+                .map(try_into_table("config"))
+                .transpose()?
+                .and_then(|config| config.get(T::name()))
+                .map(T::try_from)
+                .transpose()
+                .context(SourceProblem(layer.source.clone()))
+        })
+        .collect::<anyhow::Result<Vec<Option<T>>>>()?;
+
+    // Each item is merged into the items with lower precedence.
+    Ok(items
         .into_iter()
+        .rev()
         .flatten()
-        .map(|(name, value)| Entry::try_new(ImStr::from(name), value))
-        .collect::<Result<Vec<Entry>, _>>()
-        .context(home_config_error(config_path))?;
+        .fold(T::default(), |merged, item| item.merge(merged)))
+}
 
-    let entry_names = menu.iter().map(Entry::name).collect::<HashSet<ImStr>>();
+/// The config that a menu entry, generator, or config value is taken from.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub key: String,
+    pub source: ConfigSource,
+}
 
-    menu.extend(
-        home_menu
-            .into_iter()
-            .filter(|entry| !entry_names.contains(&entry.name())),
-    );
+/// Find the origin of every key in `layers`, sorted by key.
+fn find_origins(layers: &[Layer]) -> Vec<Origin> {
+    /// Config items whose fields are merged individually, rather than as a whole.
+    const MERGED_FIELDS: &[&str] = &["env", "display", "dmenu"];
+
+    let mut origins = Vec::new();
+    let mut seen = HashSet::default();
+    for layer in layers {
+        let mut keys = Vec::new();
+        for top in ["menu", "generator"] {
+            if let Some(Value::Table(table)) = layer.config.get(top) {
+                keys.extend(table.keys().map(|name| format!("{top}.{name}")));
+            }
+        }
+        if let Some(Value::Table(config)) = layer.config.get("config") {
+            for (name, value) in config {
+                match value {
+                    Value::Table(fields) if MERGED_FIELDS.contains(&name.as_str()) => {
+                        keys.extend(fields.keys().map(|field| format!("config.{name}.{field}")));
+                    }
+                    _ => keys.push(format!("config.{name}")),
+                }
+            }
+        }
 
-    Ok(menu)
+        // A key is taken from the layer with the highest precedence that sets it.
+        origins.extend(
+            keys.into_iter()
+                .filter(|key| seen.insert(key.clone()))
+                .map(|key| Origin {
+                    key,
+                    source: layer.source.clone(),
+                }),
+        );
+    }
+
+    origins.sort_by(|left, right| left.key.cmp(&right.key));
+    origins
 }
 
-fn try_get_config<'a, T: ConfigItem>(
-    config: &'a Value,
-    home_config: Option<&'a Value>,
-    config_path: &Path,
-) -> anyhow::Result<T> {
-    let config = config
-        .get("config")
-        .map(try_into_table("config"))
-        .transpose()
-        .context(target_config_error())?
-        .and_then(|config| config.get(T::name()))
-        .map(T::try_from)
-        .transpose()
-        .context(target_config_error())?;
+/// A parsed config, along with where it was read from.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub source: ConfigSource,
+    pub config: Value,
+}
 
-    let home_config = home_config
-        .and_then(|config| config.get("config"))
-        .map(try_into_table("config"))
-        .transpose()
-        .context(home_config_error(config_path))?
-        .and_then(|config| config.get(T::name()))
-        .map(T::try_from)
-        .transpose()
-        .context(home_config_error(config_path))?
-        .map(|config| config.merge(T::default()))
-        .unwrap_or_default();
+/// Where a config value was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Values set by command line arguments and their environment variables.
+    Args,
+    /// A pattern, read from a path or else from stdin.
+    Target(Option<PathBuf>),
+    /// The config at the path in `$DMM_CONFIG`.
+    Env(PathBuf),
+    Home(PathBuf),
+    /// A config in `/etc/xdg` or one of `$XDG_CONFIG_DIRS`.
+    System(PathBuf),
+    /// A config included by another config.
+    Include(PathBuf),
+    /// The profile with a name, from the config it's defined in.
+    Profile(String, Box<ConfigSource>),
+}
 
-    if let Some(config) = config {
-        Ok(config.merge(home_config))
-    } else {
-        Ok(home_config)
+impl ConfigSource {
+    /// The path of the config file, unless it's from stdin or the command line.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Args => None,
+            Self::Target(path) => path.as_deref(),
+            Self::Env(path) | Self::Home(path) | Self::System(path) | Self::Include(path) => {
+                Some(path)
+            }
+            Self::Profile(_, source) => source.path(),
+        }
+    }
+
+    /// A short description of the config, as written by `--show-origin`,
+    /// such as `home:/home/user/.config/dmm/config.toml`.
+    pub fn origin(&self) -> String {
+        let (kind, path) = match self {
+            Self::Args => return String::from("args"),
+            Self::Target(None) => return String::from("pattern"),
+            Self::Target(Some(path)) => ("pattern", path),
+            Self::Profile(name, source) => return format!("profile.{name}:{}", source.origin()),
+            Self::Env(path) => ("env", path),
+            Self::Home(path) => ("home", path),
+            Self::System(path) => ("system", path),
+            Self::Include(path) => ("include", path),
+        };
+        format!("{kind}:{}", path.display())
+    }
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Args => write!(f, "command line arguments"),
+            Self::Target(None) => write!(f, "provided config"),
+            Self::Target(Some(path)) => write!(
+                f,
+                "provided config `{}`",
+                style_stderr!(bold(), "{}", path.display())
+            ),
+            Self::Env(path) => write!(
+                f,
+                "config `{}` from `{}`",
+                style_stderr!(bold(), "{}", path.display()),
+                style_stderr!(bold(), "$DMM_CONFIG")
+            ),
+            Self::Home(path) => write!(
+                f,
+                "home config `{}`",
+                style_stderr!(bold(), "{}", path.display())
+            ),
+            Self::System(path) => write!(
+                f,
+                "system config `{}`",
+                style_stderr!(bold(), "{}", path.display())
+            ),
+            Self::Include(path) => write!(
+                f,
+                "included config `{}`",
+                style_stderr!(bold(), "{}", path.display())
+            ),
+            Self::Profile(name, source) => write!(
+                f,
+                "profile `{}` in {source}",
+                style_stderr!(bold(), "{name}")
+            ),
+        }
+    }
+}
+
+/// A key that dmm doesn't use, which is most likely a typo.
+#[derive(Debug, Clone)]
+pub struct UnknownKey {
+    pub key: String,
+    pub source: ConfigSource,
+    pub valid: &'static [&'static str],
+    /// Where the key is in its source file, if it could be found.
+    pub snippet: Option<String>,
+}
+
+impl UnknownKey {
+    /// The valid key most similar to the unknown key, if any is similar enough to be a typo.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        let name = self.key.rsplit('.').next().unwrap_or(&self.key);
+        let threshold = name.chars().count() / 3 + 1;
+
+        self.valid
+            .iter()
+            .map(|valid| (edit_distance(name, valid), *valid))
+            .filter(|(distance, _)| *distance <= threshold)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, valid)| valid)
+    }
+}
+
+impl Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown key `{}` in {}",
+            style_stderr!(bold(), "{}", self.key),
+            self.source
+        )?;
+
+        if let Some(suggestion) = self.suggestion() {
+            write!(
+                f,
+                "; did you mean `{}`?",
+                style_stderr!(bold(), "{suggestion}")
+            )?;
+        } else if !self.valid.is_empty() {
+            write!(f, "; valid keys are ")?;
+            for (i, valid) in self.valid.iter().enumerate() {
+                let separator = if i == 0 { "" } else { ", " };
+                write!(f, "{separator}`{}`", style_stderr!(bold(), "{valid}"))?;
+            }
+        }
+
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{snippet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The unknown keys found while parsing a config that has another problem,
+/// since an unknown key is often the cause of the problem.
+#[derive(Debug)]
+struct UnknownKeys(Vec<UnknownKey>);
+
+impl Display for UnknownKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, unknown_key) in self.0.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "\n  " };
+            write!(f, "{separator}{unknown_key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A problem with the value of the key at a key path like `menu.name.group`.
+#[derive(Debug)]
+pub struct KeyError {
+    pub key: String,
+    message: String,
+    /// Where the key is in its source file, if it could be found.
+    snippet: Option<String>,
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{snippet}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// Context for an error, naming the config the error was found in.
+#[derive(Debug)]
+struct SourceProblem(ConfigSource);
+
+impl Display for SourceProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found a problem with {}", self.0)
+    }
+}
+
+/// The text of each config, kept to show where in a file a problem was found.
+#[derive(Default)]
+struct Sources(Vec<(ConfigSource, Source)>);
+
+struct Source {
+    path: String,
+    text: String,
+}
+
+impl Sources {
+    fn get(&self, source: &ConfigSource) -> Option<&Source> {
+        self.0
+            .iter()
+            .find(|(found, _)| found == source)
+            .map(|(_, file)| file)
+    }
+
+    /// Add the location of each unknown key and the key with an invalid value to `err`.
+    fn annotate(&self, err: &mut anyhow::Error) {
+        if let Some(UnknownKeys(unknown_keys)) = err.downcast_mut::<UnknownKeys>() {
+            self.annotate_unknown_keys(unknown_keys);
+        }
+
+        let Some(SourceProblem(source)) = err.downcast_ref::<SourceProblem>() else {
+            return;
+        };
+        // The config of a profile is written within the profile's table.
+        let (source, prefix) = match source {
+            ConfigSource::Profile(name, source) => (source.as_ref(), format!("profile.{name}.")),
+            source => (source, String::new()),
+        };
+        let Some(source) = self.get(source) else {
+            return;
+        };
+        if let Some(key_error) = err.downcast_mut::<KeyError>() {
+            let key = format!("{prefix}{}", key_error.key);
+            key_error.snippet = diagnostic::find_value(&source.text, &key)
+                .map(|span| diagnostic::snippet(&source.path, &source.text, span));
+        }
+    }
+
+    fn annotate_unknown_keys(&self, unknown_keys: &mut [UnknownKey]) {
+        for unknown_key in unknown_keys {
+            let Some(source) = self.get(&unknown_key.source) else {
+                continue;
+            };
+            unknown_key.snippet = diagnostic::find_key(&source.text, &unknown_key.key)
+                .map(|span| diagnostic::snippet(&source.path, &source.text, span));
+        }
+    }
+}
+
+fn find_unknown_keys(config: &Value, source: &ConfigSource) -> Vec<UnknownKey> {
+    const TOP_KEYS: &[&str] = &[
+        "title",
+        "description",
+        "include",
+        "menu",
+        "config",
+        "generator",
+        "profile",
+    ];
+    const PROFILE_KEYS: &[&str] = &["when", "config"];
+    const WHEN_KEYS: &[&str] = &["hostname", "env"];
+
+    let mut unknown = Vec::new();
+    let mut check = |parent: &str, table: &Map<String, Value>, valid: &'static [&'static str]| {
+        for key in table.keys() {
+            if !valid.contains(&key.as_str()) {
+                let key = if parent.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{parent}.{key}")
+                };
+                unknown.push(UnknownKey {
+                    key,
+                    source: source.clone(),
+                    valid,
+                    snippet: None,
+                });
+            }
+        }
+    };
+
+    let Value::Table(config) = config else {
+        return unknown;
+    };
+    check("", config, TOP_KEYS);
+
+    if let Some(Value::Table(menu)) = config.get("menu") {
+        find_unknown_menu_keys("menu", menu, &mut check);
+    }
+
+    if let Some(Value::Table(generators)) = config.get("generator") {
+        for (name, generator) in generators {
+            if let Value::Table(generator) = generator {
+                check(&format!("generator.{name}"), generator, Generator::KEYS);
+            }
+        }
+    }
+
+    if let Some(Value::Table(config)) = config.get("config") {
+        find_unknown_config_keys("config", config, &mut check);
+    }
+
+    if let Some(Value::Table(profiles)) = config.get("profile") {
+        for (name, profile) in profiles {
+            let Value::Table(profile) = profile else {
+                continue;
+            };
+            let parent = format!("profile.{name}");
+            check(&parent, profile, PROFILE_KEYS);
+
+            if let Some(Value::Table(when)) = profile.get("when") {
+                check(&format!("{parent}.when"), when, WHEN_KEYS);
+            }
+            if let Some(Value::Table(config)) = profile.get("config") {
+                find_unknown_config_keys(&format!("{parent}.config"), config, &mut check);
+            }
+        }
+    }
+
+    unknown
+}
+
+fn find_unknown_config_keys(
+    parent: &str,
+    config: &Map<String, Value>,
+    check: &mut impl FnMut(&str, &Map<String, Value>, &'static [&'static str]),
+) {
+    /// The names of every [`ConfigItem`].
+    const CONFIG_KEYS: &[&str] = &[
+        "shell",
+        "terminal",
+        "env",
+        "detach",
+        "notify",
+        "clipboard",
+        "custom",
+        "numbered",
+        "path",
+        "desktop",
+        "sort",
+        "display",
+        "launcher",
+        "dmenu",
+    ];
+
+    check(parent, config, CONFIG_KEYS);
+
+    let items = [
+        (Shell::name(), Shell::keys()),
+        (Detach::name(), Detach::keys()),
+        (Custom::name(), Custom::keys()),
+        (Numbered::name(), Numbered::keys()),
+        (BinPath::name(), BinPath::keys()),
+        (Desktop::name(), Desktop::keys()),
+        (Sort::name(), Sort::keys()),
+        (EntryDisplay::name(), EntryDisplay::keys()),
+        (Launcher::name(), Launcher::keys()),
+        (Dmenu::name(), Dmenu::keys()),
+    ];
+    for (name, keys) in items {
+        if let Some(Value::Table(item)) = config.get(name) {
+            check(&format!("{parent}.{name}"), item, keys);
+        }
+    }
+}
+
+fn find_unknown_menu_keys(
+    parent: &str,
+    menu: &Map<String, Value>,
+    check: &mut impl FnMut(&str, &Map<String, Value>, &'static [&'static str]),
+) {
+    for (name, entry) in menu {
+        let Value::Table(entry) = entry else {
+            continue;
+        };
+        let key = format!("{parent}.{name}");
+        check(&key, entry, Entry::KEYS);
+
+        if let Some(Value::Array(args)) = entry.get("args") {
+            for (i, arg) in args.iter().enumerate() {
+                if let Value::Table(arg) = arg {
+                    check(&format!("{key}.args[{i}]"), arg, Argument::KEYS);
+                }
+            }
+        }
+
+        if let Some(Value::Table(menu)) = entry.get("menu") {
+            find_unknown_menu_keys(&format!("{key}.menu"), menu, check);
+        }
+    }
+}
+
+/// The number of single character edits needed to change `left` into `right`.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<char>>();
+    let mut previous = (0..=right.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; right.len() + 1];
+
+    for (i, l) in left.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, r) in right.iter().enumerate() {
+            let substitute = previous[j] + usize::from(l != *r);
+            current[j + 1] = substitute.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
     }
+
+    previous[right.len()]
 }
 
 fn type_error<T>(name: &str, valid: &[&str], found: &str) -> anyhow::Result<T> {
@@ -837,10 +3400,13 @@ fn type_error<T>(name: &str, valid: &[&str], found: &str) -> anyhow::Result<T> {
         }
     }
 
-    Err(anyhow!(
-        "`{}` must be of type {types}, but is of type `{}`",
-        style_stderr!(bold(), "{name}"),
-        style_stderr!(bold(), "{found}")
+    Err(key_error(
+        name,
+        format!(
+            "`{}` must be of type {types}, but is of type `{}`",
+            style_stderr!(bold(), "{name}"),
+            style_stderr!(bold(), "{found}")
+        ),
     ))
 }
 
@@ -851,6 +3417,22 @@ fn try_into_string(name: &str) -> impl Fn(&Value) -> anyhow::Result<ImStr> + '_
     }
 }
 
+/// Convert a string option, where `false` clears any value inherited from another config.
+fn try_into_clearable_string(name: &str) -> impl Fn(&Value) -> anyhow::Result<Clearable> + '_ {
+    move |value| match value {
+        Value::String(value) => Ok(Clearable::Set(ImStr::from(value))),
+        Value::Boolean(false) => Ok(Clearable::Cleared),
+        Value::Boolean(true) => Err(key_error(
+            name,
+            format!(
+                "`{}` must be a string, or false to unset it",
+                style_stderr!(bold(), "{name}")
+            ),
+        )),
+        other => type_error(name, &["string", "boolean"], other.type_str()),
+    }
+}
+
 fn try_into_boolean(name: &str) -> impl Fn(&Value) -> anyhow::Result<bool> + '_ {
     move |value| match value {
         Value::Boolean(value) => Ok(*value),
@@ -883,39 +3465,139 @@ fn try_into_array_string(name: &str) -> impl Fn(&Value) -> anyhow::Result<ImStr>
     move |value| {
         match value {
         Value::String(value) => Ok(ImStr::from(value)),
-        other => Err(anyhow!(
-            "the array `{}` must only contain elements of type `{}`, but an element is of type `{}`",
-            style_stderr!(bold(), "{name}"),
-            style_stderr!(bold(), "string"),
-            style_stderr!(bold(), "{}", other.type_str())
+        other => Err(key_error(
+            name,
+            format!(
+                "the array `{}` must only contain elements of type `{}`, but an element is of type `{}`",
+                style_stderr!(bold(), "{name}"),
+                style_stderr!(bold(), "string"),
+                style_stderr!(bold(), "{}", other.type_str())
+            ),
         )),
-        }
     }
+    }
+}
+
+/// Convert a table of environment variable names and values.
+fn try_into_env(name: &str, env: &Value) -> anyhow::Result<Vec<(ImStr, ImStr)>> {
+    try_into_table(name)(env)?
+        .iter()
+        .map(|(var, value)| {
+            let value = try_into_string(&format!("{name}.{var}"))(value)?;
+            Ok((ImStr::from(var), value))
+        })
+        .collect()
 }
 
 fn try_into_unsigned_integer(name: &str) -> impl Fn(i64) -> anyhow::Result<u64> + '_ {
     move |value| {
         value.try_into().map_err(|_| {
-            anyhow!(
-                "`{}` must be a positive integer, but is negative",
-                style_stderr!(bold(), "{name}"),
+            key_error(
+                name,
+                format!(
+                    "`{}` must be a positive integer, but is negative",
+                    style_stderr!(bold(), "{name}"),
+                ),
             )
         })
     }
 }
 
-fn home_config_error(path: &Path) -> String {
-    format!(
-        "found a problem with home config `{}`",
-        style_stderr!(bold(), "{}", path.display())
-    )
-}
-
-const fn target_config_error() -> &'static str {
-    "found a problem with provided config"
+fn key_error(key: &str, message: String) -> anyhow::Error {
+    anyhow::Error::new(KeyError {
+        key: key.to_owned(),
+        message,
+        snippet: None,
+    })
 }
 
 trait ConfigItem: for<'a> TryFrom<&'a Value, Error = anyhow::Error> + Default {
     fn name() -> &'static str;
+    /// The valid keys if the item is a table.
+    fn keys() -> &'static [&'static str];
     fn merge(self, default: Self) -> Self;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs() -> ProjectDirs {
+        ProjectDirs::from("", "", "dmm").expect("a home directory")
+    }
+
+    fn overrides(args: &[&str], env_sets: Option<&str>) -> Value {
+        let args = cli(&dirs())
+            .try_get_matches_from([&["dmm"], args, &["pattern.toml"]].concat())
+            .expect("valid arguments");
+        parse_overrides(&args, env_sets)
+            .expect("valid overrides")
+            .expect("some overrides")
+    }
+
+    #[test]
+    fn set_parses_dotted_keys_and_toml_values() {
+        let set = parse_set("config.dmenu.lines=5").unwrap();
+        assert_eq!(Value::Table(set), toml::toml! { config.dmenu.lines = 5 });
+
+        let set = parse_set("config.shell=[\"bash\", \"-c\"]").unwrap();
+        assert_eq!(
+            Value::Table(set),
+            toml::toml! { config.shell = ["bash", "-c"] }
+        );
+    }
+
+    #[test]
+    fn set_falls_back_to_a_string() {
+        let set = parse_set("config.dmenu.prompt=run:").unwrap();
+        assert_eq!(
+            Value::Table(set),
+            toml::toml! { config.dmenu.prompt = "run:" }
+        );
+
+        let set = parse_set("menu.list=1,b").unwrap();
+        assert_eq!(Value::Table(set), toml::toml! { menu.list = "1,b" });
+    }
+
+    #[test]
+    fn set_rejects_keys_that_are_not_one_path() {
+        assert!(parse_set("[a]\n[c]\nb=1").is_err());
+        assert!(parse_set("a.b\n[c]\nd=1").is_err());
+        assert!(parse_set("config.dmenu.lines").is_err());
+    }
+
+    #[test]
+    fn arguments_override_dmm_set() {
+        let env_sets = "config.dmenu.prompt=env\nconfig.dmenu.lines=4\nconfig.sort=frecency";
+        let overrides = overrides(
+            &["--lines", "8", "--set", "config.dmenu.prompt=cli"],
+            Some(env_sets),
+        );
+
+        assert_eq!(
+            overrides,
+            toml::toml! {
+                config.sort = "frecency"
+                config.dmenu.prompt = "cli"
+                config.dmenu.lines = 8
+            }
+        );
+    }
+
+    #[test]
+    fn later_sets_override_earlier_ones() {
+        let overrides = overrides(
+            &[
+                "--prompt",
+                "flag",
+                "--set",
+                "config.dmenu.prompt=first",
+                "--set",
+                "config.dmenu.prompt=second",
+            ],
+            None,
+        );
+
+        assert_eq!(overrides, toml::toml! { config.dmenu.prompt = "second" });
+    }
+}
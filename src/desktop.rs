@@ -0,0 +1,265 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ahash::HashSet;
+use anyhow::{anyhow, Context};
+use directories::BaseDirs;
+
+use crate::imstr::ImStr;
+use crate::style::{bold, style_stderr};
+
+/// An application described by a `.desktop` file.
+#[derive(Debug, Clone)]
+pub struct App {
+    pub name: ImStr,
+    pub exec: Vec<ImStr>,
+    /// Whether the application must be run inside a terminal emulator.
+    pub terminal: bool,
+}
+
+/// The XDG application directories, in order of decreasing precedence.
+pub fn xdg_dirs(base_dirs: &BaseDirs) -> Vec<PathBuf> {
+    let data_dirs = env::var_os("XDG_DATA_DIRS").filter(|dirs| !dirs.is_empty());
+    let data_dirs = data_dirs.map_or_else(
+        || {
+            vec![
+                PathBuf::from("/usr/local/share"),
+                PathBuf::from("/usr/share"),
+            ]
+        },
+        |dirs| env::split_paths(&dirs).collect(),
+    );
+
+    [base_dirs.data_dir().to_owned()]
+        .into_iter()
+        .chain(data_dirs)
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Find every application that should be displayed in `dirs`,
+/// which must be in order of decreasing precedence.
+///
+/// Any problems reading a `.desktop` file are returned alongside the applications,
+/// as a single broken file shouldn't prevent the rest from being found.
+pub fn find_apps(dirs: &[PathBuf]) -> (Vec<App>, Vec<anyhow::Error>) {
+    let current_desktops = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let current_desktops = current_desktops
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .collect::<Vec<&str>>();
+
+    let mut seen_ids = HashSet::default();
+    let mut apps = Vec::new();
+    let mut errors = Vec::new();
+
+    for dir in dirs {
+        let mut files = Vec::new();
+        find_desktop_files(dir, dir, &mut HashSet::default(), &mut files);
+
+        for (id, path) in files {
+            // Files in higher precedence directories shadow any with the same id,
+            // even if they are hidden.
+            if !seen_ids.insert(id) {
+                continue;
+            }
+
+            match parse_desktop_file(&path, &current_desktops) {
+                Ok(Some(app)) => apps.push(app),
+                Ok(None) => {}
+                Err(err) => errors.push(err.context(format!(
+                    "problem reading desktop file `{}`",
+                    style_stderr!(bold(), "{}", path.display())
+                ))),
+            }
+        }
+    }
+
+    (apps, errors)
+}
+
+/// Collect the desktop file id and path of every `.desktop` file under `dir`.
+///
+/// Symlinked directories are followed, but each directory is only searched once,
+/// which is tracked by its canonical path in `visited` so a symlink loop can't recurse forever.
+fn find_desktop_files(
+    root: &Path,
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<(String, PathBuf)>,
+) {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(read) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in read.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_desktop_files(root, &path, visited, files);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            let Some(relative) = path.strip_prefix(root).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            files.push((relative.replace('/', "-"), path));
+        }
+    }
+}
+
+/// Parse the `[Desktop Entry]` group of a `.desktop` file.
+///
+/// Returns `None` if the application shouldn't be displayed.
+fn parse_desktop_file(path: &Path, current_desktops: &[&str]) -> anyhow::Result<Option<App>> {
+    let file = fs::read_to_string(path).context("unable to read file")?;
+
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut application = false;
+    let mut terminal = false;
+
+    for line in file.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = unescape_value(value.trim());
+
+        match key.trim() {
+            "Type" => application = value == "Application",
+            "Name" => name = Some(value),
+            "Exec" => exec = Some(value),
+            "Terminal" => terminal = value == "true",
+            "NoDisplay" | "Hidden" if value == "true" => return Ok(None),
+            "OnlyShowIn" if !value.split(';').any(|de| current_desktops.contains(&de)) => {
+                return Ok(None)
+            }
+            "NotShowIn" if value.split(';').any(|de| current_desktops.contains(&de)) => {
+                return Ok(None)
+            }
+            _ => {}
+        }
+    }
+
+    let (true, Some(name), Some(exec)) = (application, name, exec) else {
+        return Ok(None);
+    };
+    let exec = parse_exec(&exec)?;
+    if exec.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(App {
+        name: ImStr::from(name),
+        exec,
+        terminal,
+    }))
+}
+
+/// Replace the escape sequences allowed in desktop file string values.
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// Split an `Exec` value into arguments, removing any field codes.
+///
+/// Arguments may be double quoted, in which case
+/// `"`, `` ` ``, `$`, and `\` must be escaped by a backslash.
+/// An argument that consists of only a field code is removed entirely.
+fn parse_exec(exec: &str) -> anyhow::Result<Vec<ImStr>> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut only_field_codes = true;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {
+                if in_arg && !(only_field_codes && arg.is_empty()) {
+                    args.push(ImStr::from(arg.as_str()));
+                }
+                arg.clear();
+                in_arg = false;
+                only_field_codes = true;
+            }
+            '"' => {
+                in_arg = true;
+                only_field_codes = false;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => arg.push(escaped),
+                            None => return Err(anyhow!("`Exec` ends with a lone backslash")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(anyhow!("`Exec` contains an unterminated quote")),
+                    }
+                }
+            }
+            '%' => {
+                in_arg = true;
+                match chars.next() {
+                    Some('%') => {
+                        arg.push('%');
+                        only_field_codes = false;
+                    }
+                    Some(_) => {}
+                    None => return Err(anyhow!("`Exec` ends with an incomplete field code")),
+                }
+            }
+            c => {
+                arg.push(c);
+                in_arg = true;
+                only_field_codes = false;
+            }
+        }
+    }
+
+    if in_arg && !(only_field_codes && arg.is_empty()) {
+        args.push(ImStr::from(arg));
+    }
+
+    Ok(args)
+}
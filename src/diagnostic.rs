@@ -0,0 +1,175 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use termcolor::{Color, ColorSpec};
+use toml_edit::{Document, Item, Table, TableLike, Value};
+
+use crate::style::{bold, style_stderr};
+
+/// Find the span of the name of the key at the key path `key` in the toml `source`.
+///
+/// Key paths are names separated by `.`, with array indices written as `[index]`.
+/// Names that contain a `.` are matched by preferring the longest name that exists.
+/// If the full path doesn't exist, the span of the deepest key that does is returned.
+pub fn find_key(source: &str, key: &str) -> Option<Range<usize>> {
+    locate(source, key, false)
+}
+
+/// Find the span of the value at the key path `key` in the toml `source`,
+/// or the span of its name if the value is a table.
+///
+/// Key paths are resolved the same way as [`find_key`].
+pub fn find_value(source: &str, key: &str) -> Option<Range<usize>> {
+    locate(source, key, true)
+}
+
+fn locate(source: &str, key: &str, prefer_value: bool) -> Option<Range<usize>> {
+    let document = Document::parse(source).ok()?;
+    let mut node = Node::Table(document.as_table());
+    let mut rest = key;
+    let mut found = None;
+
+    while !rest.is_empty() {
+        if let Some(index) = rest.strip_prefix('[') {
+            let Some((index, after)) = index.split_once(']') else {
+                break;
+            };
+            let Some(element) = index.parse().ok().and_then(|index| node.index(index)) else {
+                break;
+            };
+            node = element;
+            found = node.span().or(found);
+            rest = after.strip_prefix('.').unwrap_or(after);
+            continue;
+        }
+
+        let Some(table) = node.table_like() else {
+            break;
+        };
+
+        // Try the longest candidate name first, in case a name contains a `.`.
+        let boundaries = rest
+            .match_indices(['.', '['])
+            .map(|(i, _)| i)
+            .chain([rest.len()])
+            .rev();
+        let Some((end, key, item)) = boundaries.into_iter().find_map(|end| {
+            table
+                .get_key_value(&rest[..end])
+                .map(|(key, item)| (end, key, item))
+        }) else {
+            break;
+        };
+
+        node = Node::Item(item);
+        found = if !prefer_value || node.table_like().is_some() {
+            key.span().or_else(|| node.span()).or(found)
+        } else {
+            node.span().or_else(|| key.span()).or(found)
+        };
+        rest = &rest[end..];
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+    }
+
+    found
+}
+
+/// Render the line containing `span` with the span underlined, preceded by its location.
+///
+/// Each line is indented to line up beneath the causes of an error.
+pub fn snippet(path: &str, source: &str, span: Range<usize>) -> String {
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line = &source[line_start..line_end];
+
+    let line_number = source[..start].matches('\n').count() + 1;
+    let column = source[line_start..start].chars().count() + 1;
+    let width = source[start..span.end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    let mut blue = bold();
+    blue.set_fg(Some(Color::Blue));
+    let mut red = ColorSpec::new();
+    red.set_fg(Some(Color::Red)).set_bold(true);
+
+    let mut snippet = String::new();
+    writeln!(
+        snippet,
+        "    {gutter}{} {path}:{line_number}:{column}",
+        style_stderr!(blue, "-->")
+    )
+    .unwrap();
+    writeln!(snippet, "    {gutter} {}", style_stderr!(blue, "|")).unwrap();
+    writeln!(
+        snippet,
+        "    {} {line}",
+        style_stderr!(blue, "{line_number} |")
+    )
+    .unwrap();
+    write!(
+        snippet,
+        "    {gutter} {} {}{}",
+        style_stderr!(blue, "|"),
+        " ".repeat(column - 1),
+        style_stderr!(red, "{}", "^".repeat(width))
+    )
+    .unwrap();
+
+    snippet
+}
+
+/// The byte offset of a zero based `line` and `column`, as reported by the toml parser.
+pub fn offset(source: &str, line: usize, column: usize) -> usize {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line)
+        .map(str::len)
+        .sum::<usize>();
+    let line = &source[line_start..];
+
+    line_start
+        + line
+            .char_indices()
+            .nth(column)
+            .map_or(line.len(), |(i, _)| i)
+}
+
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Item(&'a Item),
+    Value(&'a Value),
+    Table(&'a Table),
+}
+
+impl<'a> Node<'a> {
+    fn table_like(self) -> Option<&'a dyn TableLike> {
+        match self {
+            Self::Item(item) => item.as_table_like(),
+            Self::Value(value) => value.as_inline_table().map(|table| table as &dyn TableLike),
+            Self::Table(table) => Some(table),
+        }
+    }
+
+    fn index(self, index: usize) -> Option<Self> {
+        match self {
+            Self::Item(Item::ArrayOfTables(tables)) => tables.get(index).map(Self::Table),
+            Self::Item(item) => item.as_array()?.get(index).map(Self::Value),
+            Self::Value(value) => value.as_array()?.get(index).map(Self::Value),
+            Self::Table(_) => None,
+        }
+    }
+
+    fn span(self) -> Option<Range<usize>> {
+        match self {
+            Self::Item(item) => item.span(),
+            Self::Value(value) => value.span(),
+            Self::Table(table) => table.span(),
+        }
+    }
+}
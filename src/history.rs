@@ -0,0 +1,127 @@
+use std::fmt::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ahash::HashMap;
+use anyhow::Context;
+
+use crate::imstr::ImStr;
+use crate::style::{bold, style_stderr};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+/// Entries that haven't been selected for this long are forgotten.
+const EXPIRE: u64 = 90 * DAY;
+
+/// Record of how often and how recently each menu entry was selected.
+///
+/// Stored as lines of `count<TAB>last-selected<TAB>name`,
+/// where `last-selected` is in seconds since the unix epoch.
+#[derive(Debug, Default, Clone)]
+pub struct History {
+    visits: HashMap<ImStr, Visits>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Visits {
+    count: u64,
+    last: u64,
+}
+
+impl History {
+    /// Read the history at `path`; a missing file is an empty history.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let history = match fs::read_to_string(path) {
+            Ok(history) => history,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).context(format!(
+                    "unable to read history file `{}`",
+                    style_stderr!(bold(), "{}", path.display())
+                ))
+            }
+        };
+
+        let visits = history
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let count = fields.next()?.parse().ok()?;
+                let last = fields.next()?.parse().ok()?;
+                let name = fields.next()?;
+
+                Some((ImStr::from(name), Visits { count, last }))
+            })
+            .collect();
+
+        Ok(Self { visits })
+    }
+
+    /// Write the history to `path`, forgetting any entries that have expired.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let now = now();
+        let mut history = String::new();
+        for (name, visits) in &self.visits {
+            if now.saturating_sub(visits.last) < EXPIRE {
+                writeln!(history, "{}\t{}\t{name}", visits.count, visits.last).unwrap();
+            }
+        }
+
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, history)?;
+            fs::rename(&tmp, path)
+        };
+
+        write().context(format!(
+            "unable to write history file `{}`",
+            style_stderr!(bold(), "{}", path.display())
+        ))
+    }
+
+    /// Note that the entry `name` was selected just now.
+    pub fn record(&mut self, name: ImStr) {
+        if name.contains('\n') {
+            return;
+        }
+
+        let visits = self.visits.entry(name).or_default();
+        visits.count += 1;
+        visits.last = now();
+    }
+
+    /// Rank an entry by combining how often and how recently it was selected.
+    ///
+    /// Entries that were never selected have a score of zero.
+    /// `now` is the current time as returned by [`now`].
+    pub fn score(&self, name: &str, now: u64) -> f64 {
+        self.visits.get(name).map_or(0.0, |visits| {
+            let age = now.saturating_sub(visits.last);
+            let weight = if age < HOUR {
+                4.0
+            } else if age < DAY {
+                2.0
+            } else if age < WEEK {
+                0.5
+            } else {
+                0.25
+            };
+
+            let count = visits.count as f64;
+            count * weight
+        })
+    }
+}
+
+/// The current time in seconds since the unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
@@ -1,4 +1,9 @@
 pub mod config;
+pub mod desktop;
+pub mod diagnostic;
+pub mod history;
 pub mod imstr;
+pub mod path_cache;
+pub mod patterns;
 pub mod style;
 pub mod tag;
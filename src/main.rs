@@ -1,40 +1,109 @@
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::ffi::OsString;
-use std::fs::ReadDir;
-use std::io::Write;
-use std::path::PathBuf;
+use std::fs::{OpenOptions, ReadDir};
+use std::io::{self, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::{env, fs, panic, process, thread};
+use std::rc::Rc;
+use std::{env, fs, iter, panic, process, thread};
 
 use ahash::HashMap;
 use anyhow::{anyhow, Context};
 use is_executable::IsExecutable;
 use termcolor::{Color, ColorSpec, StandardStream};
 
-use dmm::config::{self, BinPath, Config, Custom, Entry, Run, Shell};
+use dmm::config::{
+    self, is_wayland, set_value, Argument, BinPath, Clearable, Config, Custom, Desktop, Detach,
+    Dmenu, Entry, Generator, Launcher, Notify, Output, Run, RunOptions, Shell, Sort, Submenu,
+};
+use dmm::desktop;
+use dmm::history::{self, History};
 use dmm::imstr::ImStr;
+use dmm::path_cache::{DirListing, PathCache};
+use dmm::patterns;
 use dmm::style::{bold, stderr_color_choice, style_stderr, write_style};
-use dmm::tag::{Binary, Decimal, Tag};
+use dmm::tag::{self, Binary, Decimal, Tag};
 
 #[derive(Debug, Clone)]
 struct RunEntry {
     name: ImStr,
-    run: Run,
+    action: Action,
     group: i64,
+    description: Option<ImStr>,
+    keywords: Rc<[ImStr]>,
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Run(Job),
+    Prompt(Job, Rc<[Argument]>),
+    Menu(Rc<Submenu>),
+    Back,
+}
+
+/// A selected command, and how it should be executed.
+#[derive(Debug, Clone)]
+struct Job {
+    run: Run,
+    options: RunOptions,
+}
+
+impl Job {
+    fn new(run: Run) -> Self {
+        Self {
+            run,
+            options: RunOptions::default(),
+        }
+    }
 }
 
 impl RunEntry {
     fn try_from(entry: Entry, shell_is_enabled: bool) -> Option<Self> {
         match entry {
-            Entry::Full { name, run, group } => Some(Self { name, run, group }),
+            Entry::Full {
+                name,
+                run,
+                group,
+                description,
+                keywords,
+                args,
+                options,
+            } => Some(Self {
+                name,
+                action: if args.is_empty() {
+                    Action::Run(Job { run, options })
+                } else {
+                    Action::Prompt(Job { run, options }, args)
+                },
+                group,
+                description,
+                keywords,
+            }),
+            Entry::Menu {
+                name,
+                menu,
+                group,
+                description,
+                keywords,
+            } => Some(Self {
+                name,
+                action: Action::Menu(menu),
+                group,
+                description,
+                keywords,
+            }),
             Entry::Name(name) => Some(Self {
-                run: if shell_is_enabled {
+                action: Action::Run(Job::new(if shell_is_enabled {
                     Run::Shell(name.clone())
                 } else {
                     Run::binary(name.clone())
-                },
+                })),
                 name,
                 group: 0,
+                description: None,
+                keywords: Rc::default(),
             }),
             Entry::Filter(_) => None,
         }
@@ -43,7 +112,40 @@ impl RunEntry {
 
 fn main() {
     if let Err(err) = (|| -> anyhow::Result<()> {
-        let config = config::get()?;
+        let mut config = config::get()?;
+
+        if config.args.subcommand_matches("check").is_some() {
+            for unknown_key in &config.unknown_keys {
+                display_error(&anyhow!("{unknown_key}"));
+            }
+            if !config.unknown_keys.is_empty() {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+
+        if let Some(dir) = config.pick_dir() {
+            let picked = if config.numbered.is_enabled() {
+                pick_pattern::<Decimal>(config, &dir)?
+            } else {
+                pick_pattern::<Binary>(config, &dir)?
+            };
+            let Some(picked) = picked else {
+                return Ok(());
+            };
+            config = picked;
+        }
+
+        for unknown_key in &config.unknown_keys {
+            warn_error(&anyhow!("{unknown_key}"));
+        }
+
+        if config.args.get_flag("show-origin") {
+            for origin in &config.origins {
+                println!("{}\t{}", origin.source.origin(), origin.key);
+            }
+            return Ok(());
+        }
 
         let commands = if config.numbered.is_enabled() {
             get_selection::<Decimal>(&config)?
@@ -51,31 +153,149 @@ fn main() {
             get_selection::<Binary>(&config)?
         };
 
-        run_commands(&commands, &config)
+        if config.args.get_flag("dry-run") {
+            log_commands(&commands, &config);
+        }
+        if let Some(format) = config.args.get_one::<String>("print") {
+            print_commands(&commands, &config, format);
+        }
+        if is_preview(&config) {
+            return Ok(());
+        }
+
+        run_commands(&commands, &config);
+        Ok(())
     })() {
         display_error(&err);
         process::exit(1);
     }
 }
 
-fn get_selection<T: Tag>(config: &Config) -> anyhow::Result<Vec<Run>> {
-    let entries = build_entries(config)?;
-    let menu_display = display_entries::<T>(config, &entries);
-    let choices = run_dmenu(menu_display, &config.dmenu.args()).context("problem running dmenu")?;
-    let choices = choices
-        .split('\n')
-        .filter(|choice| !choice.trim().is_empty());
+/// Whether commands are only printed or described, with `--print` or `--dry-run`.
+fn is_preview(config: &Config) -> bool {
+    config.args.get_one::<String>("print").is_some() || config.args.get_flag("dry-run")
+}
+
+/// Choose a pattern in `dir` with the launcher, then load it in place of any other pattern.
+///
+/// Returns `None` if no pattern is chosen.
+fn pick_pattern<T: Tag>(config: Config, dir: &Path) -> anyhow::Result<Option<Config>> {
+    let patterns = patterns::find(dir)?;
+    if patterns.is_empty() {
+        return Err(anyhow!(
+            "no patterns were found in `{}`",
+            style_stderr!(bold(), "{}", dir.display())
+        ));
+    }
 
-    let commands = choices
-        .filter_map(|choice| {
+    let mut display = String::new();
+    for (i, pattern) in patterns.iter().enumerate() {
+        if config.numbered.is_enabled() {
+            T::push_tag(i, &mut display);
+            display.push_str(config.numbered.separator());
+            display.push_str(&pattern.display());
+        } else {
+            display.push_str(&pattern.display());
+            T::push_tag(i, &mut display);
+        }
+        display.push('\n');
+    }
+    let dmenu = Dmenu {
+        prompt: Some(Clearable::Set(ImStr::from(
+            set_value(&config.dmenu.prompt).unwrap_or("pattern:"),
+        ))),
+        ..config.dmenu.clone()
+    };
+    let choice = run_launcher(display, config.launcher, &dmenu.args(config.launcher))
+        .context(format!("problem running {}", config.launcher.command()))?;
+
+    let Some(pattern) = choice
+        .lines()
+        .find_map(|choice| patterns.get(T::pop_tag(choice)?))
+    else {
+        return Ok(None);
+    };
+    config.with_pattern(&pattern.path).map(Some)
+}
+
+fn get_selection<T: Tag>(config: &Config) -> anyhow::Result<Vec<Job>> {
+    // History is only needed to sort by frecency, so it's otherwise never read or written.
+    let frecency = config.sort == Sort::Frecency;
+    let history_path = config.dirs.cache_dir().join("history");
+    let mut history = if frecency {
+        History::load(&history_path).unwrap_or_else(|err| {
+            warn_error(&err);
+            History::default()
+        })
+    } else {
+        History::default()
+    };
+    let mut commands = Vec::new();
+    let mut opened = Vec::<Rc<Submenu>>::new();
+    let mut warned_descriptions = false;
+
+    loop {
+        let (entries, dmenu) = if let Some(menu) = opened.last() {
+            let dmenu = Dmenu {
+                prompt: menu
+                    .prompt
+                    .clone()
+                    .map(Clearable::Set)
+                    .or_else(|| config.dmenu.prompt.clone()),
+                ..config.dmenu.clone()
+            };
+            (build_submenu_entries(config, &history, menu), dmenu)
+        } else {
+            (build_entries(config, &history)?, config.dmenu.clone())
+        };
+
+        if !warned_descriptions
+            && !config.display.search_description()
+            && config.launcher != Launcher::Rofi
+            && entries.iter().any(|entry| entry.description.is_some())
+        {
+            warn_error(&anyhow!(
+                "`{}` only affects rofi, so {} still searches descriptions",
+                style_stderr!(bold(), "config.display.description = false"),
+                config.launcher.command()
+            ));
+            warned_descriptions = true;
+        }
+
+        let menu_display = display_entries::<T>(config, &entries);
+        let choices = run_launcher(menu_display, config.launcher, &dmenu.args(config.launcher))
+            .context(format!("problem running {}", config.launcher.command()))?;
+        let choices = choices
+            .split('\n')
+            .filter(|choice| !choice.trim().is_empty());
+
+        let mut next = None;
+        for choice in choices {
             if let Some(id) = T::pop_tag(choice) {
                 let entry = entries
                     .get(id)
                     .expect("logic error: mismatch between entry tag and entry index");
 
-                Some(entry.run.clone())
+                match &entry.action {
+                    Action::Run(job) => {
+                        commands.extend(follow_output(config, &dmenu, &entry.name, job.clone())?);
+                    }
+                    Action::Prompt(job, args) => {
+                        if let Some(run) = prompt_args(config, &dmenu, &job.run, args)? {
+                            let job = Job {
+                                run,
+                                options: job.options.clone(),
+                            };
+                            commands.extend(follow_output(config, &dmenu, &entry.name, job)?);
+                        }
+                    }
+                    action => next = Some(action.clone()),
+                }
+                if frecency && !matches!(entry.action, Action::Back) {
+                    history.record(entry.name.clone());
+                }
             } else if let Custom::Enabled = config.custom {
-                Some(Run::Shell(choice.into()))
+                commands.push(Job::new(Run::Shell(choice.into())));
             } else {
                 let err = anyhow!(
                     "ad-hoc commands are disabled; consider setting `config.custom = true`"
@@ -86,142 +306,488 @@ fn get_selection<T: Tag>(config: &Config) -> anyhow::Result<Vec<Run>> {
                 ));
 
                 warn_error(&err);
-                None
             }
-        })
-        .collect();
+        }
+
+        match next {
+            Some(Action::Menu(menu)) => opened.push(menu),
+            Some(Action::Back) => {
+                opened.pop();
+            }
+            _ => break,
+        }
+    }
+
+    // Nothing is run with `--print` or `--dry-run`, so nothing was really selected.
+    if frecency && !is_preview(config) {
+        if let Err(err) = history.save(&history_path) {
+            warn_error(&err);
+        }
+    }
 
     Ok(commands)
 }
 
-fn build_entries(config: &Config) -> anyhow::Result<Vec<RunEntry>> {
-    let mut entries = if let BinPath::Enabled {
+/// Prompt for the value of each argument in turn, substituting them into `run`.
+///
+/// Returns `None` if any prompt is cancelled.
+fn prompt_args(
+    config: &Config,
+    dmenu: &Dmenu,
+    run: &Run,
+    args: &[Argument],
+) -> anyhow::Result<Option<Run>> {
+    let mut values = Vec::with_capacity(args.len());
+
+    for arg in args {
+        let dmenu = Dmenu {
+            prompt: Some(Clearable::Set(
+                arg.prompt
+                    .clone()
+                    .unwrap_or_else(|| ImStr::from(format!("{}:", arg.name))),
+            )),
+            ..dmenu.clone()
+        };
+        let choices = arg
+            .choices
+            .iter()
+            .fold(String::new(), |mut choices, choice| {
+                choices.push_str(choice);
+                choices.push('\n');
+                choices
+            });
+
+        let value = run_launcher(choices, config.launcher, &dmenu.args(config.launcher))
+            .context(format!("problem running {}", config.launcher.command()))?;
+        let Some(value) = value.lines().find(|value| !value.trim().is_empty()) else {
+            return Ok(None);
+        };
+
+        values.push((arg.placeholder(), value.to_owned()));
+    }
+
+    let values = values
+        .iter()
+        .map(|(placeholder, value)| (placeholder.as_str(), value.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    Ok(Some(run.substitute(&values)))
+}
+
+/// If `job` has `output = "menu"`, run it and show its output in another menu,
+/// returning a job for each selected line if the entry has `each`.
+/// Any other job is returned as is, as is every job with `--print` or `--dry-run`,
+/// since running it to show its output could have side effects.
+fn follow_output(config: &Config, dmenu: &Dmenu, name: &str, job: Job) -> anyhow::Result<Vec<Job>> {
+    let Output::Menu { each } = &job.options.output else {
+        return Ok(vec![job]);
+    };
+    if is_preview(config) {
+        return Ok(vec![job]);
+    }
+
+    let output = match capture_output(&job, config) {
+        Ok(output) => output,
+        Err(err) => {
+            warn_error(&err.context(format!(
+                "problem running entry `{}`",
+                style_stderr!(bold(), "{name}")
+            )));
+            return Ok(Vec::new());
+        }
+    };
+
+    let dmenu = Dmenu {
+        prompt: Some(Clearable::Set(ImStr::from(format!("{name}:")))),
+        ..dmenu.clone()
+    };
+    let choices = run_launcher(
+        output.clone(),
+        config.launcher,
+        &dmenu.args(config.launcher),
+    )
+    .context(format!("problem running {}", config.launcher.command()))?;
+
+    let Some(each) = each else {
+        return Ok(Vec::new());
+    };
+    let options = RunOptions {
+        output: Output::Ignore,
+        ..job.options.clone()
+    };
+
+    let mut jobs = Vec::new();
+    for line in choices.lines().filter(|line| !line.trim().is_empty()) {
+        // A line that wasn't in the output was typed in, like an ad-hoc command.
+        if !output.lines().any(|output| output == line) && !matches!(config.custom, Custom::Enabled)
+        {
+            let err =
+                anyhow!("ad-hoc commands are disabled; consider setting `config.custom = true`")
+                    .context(format!(
+                        "can't run `{}` with `{}`",
+                        style_stderr!(&bold(), "{line}"),
+                        style_stderr!(&bold(), "{name}")
+                    ));
+            warn_error(&err);
+            continue;
+        }
+
+        jobs.push(Job {
+            run: each.substitute(&[("{}", line)]),
+            options: options.clone(),
+        });
+    }
+
+    Ok(jobs)
+}
+
+fn build_entries(config: &Config, history: &History) -> anyhow::Result<Vec<RunEntry>> {
+    let mut entries = Vec::new();
+    let mut menu_entries = config
+        .entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.name(),
+                RunEntry::try_from(entry.clone(), !config.shell.is_enabled()),
+            )
+        })
+        .collect::<HashMap<ImStr, Option<RunEntry>>>();
+
+    for generator in &config.generators {
+        let generated = match generate_entries(config, generator) {
+            Ok(generated) => generated,
+            Err(err) => {
+                warn_error(&err);
+                continue;
+            }
+        };
+
+        for entry in generated {
+            let is_name = matches!(entry, Entry::Name(_));
+            let mut run_entry = RunEntry::try_from(entry.clone(), !config.shell.is_enabled());
+            if let (true, Some(run_entry)) = (is_name, &mut run_entry) {
+                run_entry.group = generator.group;
+            }
+
+            menu_entries.entry(entry.name()).or_insert(run_entry);
+        }
+    }
+
+    if let BinPath::Enabled { replace, group, .. } = &config.path {
+        let bins = find_path_bins(config)?;
+        merge_source(&mut entries, &mut menu_entries, bins, *replace, *group);
+    }
+
+    if let Desktop::Enabled {
         path,
-        env,
+        xdg,
         replace,
-        recursive,
         group,
-    } = &config.path
+    } = &config.desktop
     {
-        let mut entries = Vec::new();
-        let mut menu_entries = config
-            .entries
+        let mut dirs = path
             .iter()
-            .map(|entry| {
-                (
-                    entry.name(),
-                    RunEntry::try_from(entry.clone(), !config.shell.is_enabled()),
-                )
-            })
-            .collect::<HashMap<ImStr, Option<RunEntry>>>();
+            .map(|path| expand_home(config, path))
+            .collect::<Vec<PathBuf>>();
+        if *xdg {
+            dirs.extend(desktop::xdg_dirs(&config.base_dirs));
+        }
 
-        let env_paths = env.then(|| env::var_os("PATH")).flatten();
-        let env_paths = env_paths
-            .as_ref()
-            .map(env::split_paths)
-            .into_iter()
-            .flatten();
+        let (apps, errors) = desktop::find_apps(&dirs);
+        for err in errors {
+            warn_error(&err);
+        }
 
-        let paths = path
-            .iter()
-            .map(|pathstr| {
-                if pathstr.starts_with("~/") {
-                    let start = '~'.len_utf8() + '/'.len_utf8();
-                    let mut path = PathBuf::new();
-                    path.push(config.base_dirs.home_dir());
-                    path.push(&pathstr[start..]);
-                    path
-                } else {
-                    PathBuf::from(pathstr.as_str())
-                }
+        let apps = apps
+            .into_iter()
+            .map(|app| {
+                let job = Job {
+                    run: Run::Bare(app.exec),
+                    options: RunOptions {
+                        terminal: app.terminal,
+                        ..RunOptions::default()
+                    },
+                };
+                (app.name, job)
             })
-            .chain(env_paths);
+            .collect();
+        merge_source(&mut entries, &mut menu_entries, apps, *replace, *group);
+    }
 
-        let path_bins = paths.filter_map(|path| {
-            let mut files = Vec::new();
-            let mut recur = Vec::new();
+    entries.extend(menu_entries.into_values().flatten());
 
-            match fs::read_dir(&path) {
-                Ok(dir) => {
-                    if let Err(err) = walk_dir(dir, &mut recur, &mut files) {
-                        return Some(Err(err));
-                    }
-                }
-                Err(_) => return None,
-            }
+    sort_entries(&mut entries, config.sort, history);
 
-            if *recursive {
-                while let Some(path) = recur.pop() {
-                    match fs::read_dir(&path) {
-                        Ok(dir) => {
-                            if let Err(err) = walk_dir(dir, &mut recur, &mut files) {
-                                return Some(Err(err));
-                            }
-                        }
-                        Err(_) => continue,
-                    }
-                }
+    Ok(entries)
+}
+
+/// Run `generator`, or read its cached output, and parse the output into entries.
+fn generate_entries(config: &Config, generator: &Generator) -> anyhow::Result<Vec<Entry>> {
+    // Names that only differ in characters that were replaced are told apart by the hash.
+    let file_name = generator
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let file_name = format!("{file_name}-{:016x}", fnv_hash(&generator.name));
+    let cache_path = config.dirs.cache_dir().join("generator").join(file_name);
+    // The cache is only valid for the command that produced it.
+    let header = format!("{:?}", generator.run.to_string());
+
+    let cached = generator
+        .cache
+        .and_then(|ttl| read_generator_cache(&cache_path, &header, ttl));
+    let output = if let Some(output) = cached {
+        output
+    } else {
+        let output = capture_output(&Job::new(generator.run.clone()), config).context(format!(
+            "problem running generator `{}`",
+            style_stderr!(bold(), "generator.{}", generator.name)
+        ))?;
+
+        if generator.cache.is_some() {
+            let write = || -> std::io::Result<()> {
+                fs::create_dir_all(config.dirs.cache_dir().join("generator"))?;
+                fs::write(&cache_path, format!("{header}\n{output}"))
+            };
+            if let Err(err) = write() {
+                warn_error(&anyhow!(err).context(format!(
+                    "unable to write generator cache `{}`",
+                    style_stderr!(bold(), "{}", cache_path.display())
+                )));
             }
+        }
 
-            Some(Ok(files))
-        });
+        output
+    };
 
-        for bins in path_bins {
-            let bins = bins?;
-            let mut bin_entries = Vec::new();
+    generator.parse_output(&output)
+}
 
-            for (path, name) in bins {
-                let path = path.into_string().map_err(|path| {
-                    anyhow!(
-                        "the path `{}` contained invalid unicode",
-                        style_stderr!(bold(), "{}", path.to_string_lossy())
-                    )
-                });
-                let path = match path {
-                    Ok(path) => ImStr::from(path),
-                    Err(err) => {
-                        warn_error(&err);
-                        continue;
-                    }
-                };
+/// Hash `text` with 64 bit FNV-1a, which, unlike the std hashers, is stable across runs and versions.
+fn fnv_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
 
-                if menu_entries.contains_key(&name) {
-                    if *replace {
-                        let menu_entry = menu_entries.get_mut(&name).expect("unreachable");
-                        if menu_entry.is_some() {
-                            let run_entry = menu_entry.take().expect("unreachable");
-                            bin_entries.push(RunEntry {
-                                name,
-                                run: Run::binary(path),
-                                group: run_entry.group,
-                            });
-                        }
-                    }
-                } else {
-                    bin_entries.push(RunEntry {
+fn read_generator_cache(path: &Path, header: &str, ttl: u64) -> Option<String> {
+    let age = fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+    if age.as_secs() >= ttl {
+        return None;
+    }
+
+    let cache = fs::read_to_string(path).ok()?;
+    let (cached_header, output) = cache.split_once('\n')?;
+    (cached_header == header).then(|| output.to_owned())
+}
+
+/// Run `run` to completion, returning its stdout.
+fn capture_output(job: &Job, config: &Config) -> anyhow::Result<String> {
+    let run = &job.run;
+    let invocation = Invocation::try_new(job, config)?.context("the command is empty")?;
+    let stdin = invocation.stdin.clone();
+
+    let mut child = invocation
+        .command()
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!(
+            "failed to run command `{}`",
+            style_stderr!(bold(), "{run}")
+        ))?;
+
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())
+            .context("failed to write to shell stdin??")?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .context(format!("failed to read `{}` stdout??", run))?;
+    if !output.status.success() {
+        let err = anyhow!(
+            "`{}` failed with {}",
+            style_stderr!(bold(), "{run}"),
+            output.status
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+
+        return Err(if stderr.is_empty() {
+            err
+        } else {
+            anyhow!("{stderr}").context(err)
+        });
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Add entries found from a source such as `config.path` to `entries`.
+///
+/// A found entry with the same name as an entry from `menu` is dropped,
+/// unless `replace` is true, in which case it replaces the menu entry but keeps its group.
+fn merge_source(
+    entries: &mut Vec<RunEntry>,
+    menu_entries: &mut HashMap<ImStr, Option<RunEntry>>,
+    found: Vec<(ImStr, Job)>,
+    replace: bool,
+    group: i64,
+) {
+    for (name, job) in found {
+        if let Some(menu_entry) = menu_entries.get_mut(&name) {
+            if replace {
+                if let Some(menu_entry) = menu_entry.take() {
+                    entries.push(RunEntry {
                         name,
-                        run: Run::binary(path),
-                        group: *group,
+                        action: Action::Run(job),
+                        group: menu_entry.group,
+                        description: menu_entry.description,
+                        keywords: menu_entry.keywords,
                     });
                 }
             }
-
-            entries.extend(bin_entries);
+        } else {
+            entries.push(RunEntry {
+                name,
+                action: Action::Run(job),
+                group,
+                description: None,
+                keywords: Rc::default(),
+            });
         }
+    }
+}
 
-        entries.extend(menu_entries.into_iter().filter_map(|(_, entry)| entry));
-
-        entries
+/// Replace a leading `~/` in `pathstr` with the path to the home directory.
+fn expand_home(config: &Config, pathstr: &str) -> PathBuf {
+    if pathstr.starts_with("~/") {
+        let start = '~'.len_utf8() + '/'.len_utf8();
+        let mut path = PathBuf::new();
+        path.push(config.base_dirs.home_dir());
+        path.push(&pathstr[start..]);
+        path
     } else {
-        config
-            .entries
-            .iter()
-            .filter_map(|entry| RunEntry::try_from(entry.clone(), !config.shell.is_enabled()))
-            .collect::<Vec<RunEntry>>()
+        PathBuf::from(pathstr)
+    }
+}
+
+fn find_path_bins(config: &Config) -> anyhow::Result<Vec<(ImStr, Job)>> {
+    let BinPath::Enabled {
+        path,
+        env,
+        recursive,
+        cache,
+        ..
+    } = &config.path
+    else {
+        return Ok(Vec::new());
     };
 
+    let env_paths = env.then(|| env::var_os("PATH")).flatten();
+    let env_paths = env_paths
+        .as_ref()
+        .map(env::split_paths)
+        .into_iter()
+        .flatten();
+
+    let paths = path
+        .iter()
+        .map(|pathstr| expand_home(config, pathstr))
+        .chain(env_paths);
+
+    let cache_path = config.dirs.cache_dir().join("path");
+    let mut cache = cache.then(|| {
+        PathCache::load(&cache_path).unwrap_or_else(|err| {
+            warn_error(&err);
+            PathCache::default()
+        })
+    });
+
+    let mut files = Vec::new();
+    for path in paths {
+        let mut recur = Vec::new();
+
+        match read_bin_dir(&path, cache.as_mut())? {
+            Some(listing) => push_listing(&path, listing, &mut recur, &mut files),
+            None => continue,
+        }
+
+        if *recursive {
+            while let Some(path) = recur.pop() {
+                if let Some(listing) = read_bin_dir(&path, cache.as_mut())? {
+                    push_listing(&path, listing, &mut recur, &mut files);
+                }
+            }
+        }
+    }
+
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&cache_path) {
+            warn_error(&err);
+        }
+    }
+
+    let bins = files
+        .into_iter()
+        .filter_map(|(path, name)| match path.into_string() {
+            Ok(path) => Some((name, Job::new(Run::binary(ImStr::from(path))))),
+            Err(path) => {
+                warn_error(&anyhow!(
+                    "the path `{}` contained invalid unicode",
+                    style_stderr!(bold(), "{}", path.to_string_lossy())
+                ));
+                None
+            }
+        })
+        .collect();
+
+    Ok(bins)
+}
+
+fn build_submenu_entries(config: &Config, history: &History, menu: &Submenu) -> Vec<RunEntry> {
+    let mut entries = menu
+        .entries
+        .iter()
+        .filter_map(|entry| RunEntry::try_from(entry.clone(), !config.shell.is_enabled()))
+        .collect::<Vec<RunEntry>>();
+
+    sort_entries(&mut entries, config.sort, history);
+
+    if let Some(back) = &menu.back {
+        entries.insert(
+            0,
+            RunEntry {
+                name: back.clone(),
+                action: Action::Back,
+                group: 0,
+                description: None,
+                keywords: Rc::default(),
+            },
+        );
+    }
+
+    entries
+}
+
+fn sort_entries(entries: &mut [RunEntry], sort: Sort, history: &History) {
+    let now = history::now();
     entries.sort_unstable_by(|l, r| {
         let by_group = l.group.cmp(&r.group).reverse();
+        let by_frecency = || match sort {
+            Sort::Name => Ordering::Equal,
+            Sort::Frecency => history
+                .score(&l.name, now)
+                .total_cmp(&history.score(&r.name, now))
+                .reverse(),
+        };
         let by_lowercase_name = || {
             l.name
                 .to_ascii_lowercase()
@@ -229,17 +795,63 @@ fn build_entries(config: &Config) -> anyhow::Result<Vec<RunEntry>> {
         };
         let by_name = || l.name.cmp(&r.name);
 
-        by_group.then_with(by_lowercase_name).then_with(by_name)
+        by_group
+            .then_with(by_frecency)
+            .then_with(by_lowercase_name)
+            .then_with(by_name)
     });
+}
 
-    Ok(entries)
+/// List the executables and subdirectories in `dir`,
+/// reusing the cached listing if `dir` hasn't been modified since it was cached.
+///
+/// Returns `None` if `dir` can't be read.
+fn read_bin_dir(dir: &Path, cache: Option<&mut PathCache>) -> anyhow::Result<Option<DirListing>> {
+    let modified = fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let mut cache = cache.zip(modified);
+    if let Some((cache, modified)) = &mut cache {
+        if let Some(listing) = cache.get(dir, *modified) {
+            return Ok(Some(listing.clone()));
+        }
+    }
+
+    let Ok(read) = fs::read_dir(dir) else {
+        return Ok(None);
+    };
+    let mut listing = DirListing::default();
+    walk_dir(read, &mut listing)?;
+
+    if let Some((cache, modified)) = cache {
+        cache.insert(dir.to_owned(), modified, listing.clone());
+    }
+
+    Ok(Some(listing))
 }
 
-fn walk_dir(
-    dir: ReadDir,
+fn push_listing(
+    dir: &Path,
+    listing: DirListing,
     recur: &mut Vec<PathBuf>,
     files: &mut Vec<(OsString, ImStr)>,
-) -> anyhow::Result<()> {
+) {
+    recur.extend(
+        listing
+            .subdirs
+            .iter()
+            .map(|subdir| dir.join(subdir.as_str())),
+    );
+    files.extend(
+        listing
+            .executables
+            .into_iter()
+            .map(|name| (dir.join(name.as_str()).into_os_string(), name)),
+    );
+}
+
+fn walk_dir(dir: ReadDir, listing: &mut DirListing) -> anyhow::Result<()> {
     for entry in dir {
         let entry = entry.context("error trying to walk PATH directory")?;
         let filetype = entry.file_type().context("error reading file metadata")?;
@@ -256,13 +868,26 @@ fn walk_dir(
                 })
         };
 
-        if filetype.is_dir() || follow_symlink_is_dir() {
-            recur.push(entry.path());
-        } else if entry.path().is_executable() {
-            files.push((
-                entry.path().into_os_string(),
-                entry.file_name().to_string_lossy().into(),
-            ));
+        let is_dir = filetype.is_dir() || follow_symlink_is_dir();
+        if !is_dir && !entry.path().is_executable() {
+            continue;
+        }
+
+        let name = match entry.file_name().into_string() {
+            Ok(name) => ImStr::from(name),
+            Err(_) => {
+                warn_error(&anyhow!(
+                    "the path `{}` contained invalid unicode",
+                    style_stderr!(bold(), "{}", entry.path().display())
+                ));
+                continue;
+            }
+        };
+
+        if is_dir {
+            listing.subdirs.push(name);
+        } else {
+            listing.executables.push(name);
         }
     }
 
@@ -270,30 +895,98 @@ fn walk_dir(
 }
 
 fn display_entries<T: Tag>(config: &Config, entries: &[RunEntry]) -> String {
+    /// The number of spaces before keywords, enough to push them past the edge of most menus.
+    const KEYWORD_GAP: usize = 200;
+
     let mut display = String::new();
 
-    if config.numbered.is_enabled() {
-        for (i, entry) in entries.iter().enumerate() {
-            T::push_tag(i, &mut display);
-            display.push_str(config.numbered.separator());
-            display.push_str(&entry.name);
-            display.push('\n');
+    let labels = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mut label = String::new();
+            if config.numbered.is_enabled() {
+                T::push_tag(i, &mut label);
+                label.push_str(config.numbered.separator());
+                label.push_str(&entry.name);
+            } else {
+                label.push_str(&entry.name);
+                T::push_tag(i, &mut label);
+            }
+            label
+        })
+        .collect::<Vec<String>>();
+    // Descriptions are aligned in a column after the longest label that has one.
+    let width = entries
+        .iter()
+        .zip(&labels)
+        .filter(|(entry, _)| entry.description.is_some())
+        .map(|(_, label)| tag::visible_width(label))
+        .max()
+        .unwrap_or(0);
+    // Rofi can display text that isn't searched, using the `display` row option,
+    // and search text that isn't displayed, using the `meta` row option.
+    let rofi = config.launcher == Launcher::Rofi;
+    let hide_descriptions = rofi && !config.display.search_description();
+    // Other launchers only search the text of each line, so keywords are appended to it.
+    // A vertical menu cuts off long lines, which hides keywords after a wide enough gap,
+    // but the gap would fill a horizontal menu with a single entry, so they aren't added there.
+    let vertical = config.dmenu.lines.is_some_and(|lines| lines > 0)
+        || !matches!(config.launcher, Launcher::Dmenu | Launcher::Bemenu);
+
+    for (entry, label) in entries.iter().zip(&labels) {
+        display.push_str(label);
+
+        // Rofi row options start with a null byte, and are separated by a unit separator.
+        let mut option_separator = '\0';
+        if let Some(description) = &entry.description {
+            if hide_descriptions {
+                display.push(option_separator);
+                display.push_str("display\x1f");
+                display.push_str(label);
+                option_separator = '\x1f';
+            }
+            let padding = width - tag::visible_width(label);
+            display.extend(iter::repeat_n(' ', padding));
+            display.push_str(" — ");
+            display.push_str(description);
         }
-    } else {
-        for (i, entry) in entries.iter().enumerate() {
-            display.push_str(&entry.name);
-            T::push_tag(i, &mut display);
-            display.push('\n');
+
+        if !entry.keywords.is_empty() && (rofi || vertical) {
+            if rofi {
+                display.push(option_separator);
+                display.push_str("meta\x1f");
+            } else {
+                display.extend(iter::repeat_n(' ', KEYWORD_GAP));
+            }
+            for (i, keyword) in entry.keywords.iter().enumerate() {
+                if i > 0 {
+                    display.push(' ');
+                }
+                display.push_str(keyword);
+            }
         }
+        display.push('\n');
     }
 
     display
 }
 
-fn run_dmenu(menu_display: String, dmenu_args: &[Cow<'_, str>]) -> anyhow::Result<String> {
-    let mut dmenu = Command::new("dmenu")
+fn run_launcher(
+    menu_display: String,
+    launcher: Launcher,
+    launcher_args: &[Cow<'_, str>],
+) -> anyhow::Result<String> {
+    let name = launcher.command();
+    // fzf draws its interface on stderr, which must stay connected to the terminal.
+    let stderr = if launcher == Launcher::Fzf {
+        Stdio::inherit()
+    } else {
+        Stdio::piped()
+    };
+    let mut launcher = Command::new(name)
         .args(
-            dmenu_args
+            launcher_args
                 .iter()
                 .map(Cow::as_ref)
                 .collect::<Vec<&str>>()
@@ -301,113 +994,441 @@ fn run_dmenu(menu_display: String, dmenu_args: &[Cow<'_, str>]) -> anyhow::Resul
         )
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(stderr)
         .spawn()
         .context(format!(
             "failed to run command `{}` (is it installed?)",
-            style_stderr!(bold(), "dmenu")
+            style_stderr!(bold(), "{name}")
         ))?;
-    let mut stdin = dmenu
+    let mut stdin = launcher
         .stdin
         .take()
-        .context("failed to establish pipe to dmenu??")?;
+        .context(format!("failed to establish pipe to {name}??"))?;
 
     let thread = thread::spawn(move || {
         stdin
             .write_all(menu_display.as_bytes())
-            .context("failed to write to dmenu stdin??")
+            .context(format!("failed to write to {name} stdin??"))
     });
     match thread.join() {
         Ok(result) => result?,
         Err(err) => panic::resume_unwind(err),
     }
 
-    let output = dmenu
+    let output = launcher
         .wait_with_output()
-        .context("failed to read dmenu stdout??")?;
+        .context(format!("failed to read {name} stdout??"))?;
 
     Ok(String::from_utf8(output.stdout)?)
 }
 
-fn run_commands(commands: &[Run], config: &Config) -> anyhow::Result<()> {
-    for command in commands {
-        match command {
-            Run::Bare(run) => {
-                if let Some(bin) = run.first() {
-                    let args = &run[1..].iter().map(ImStr::as_str).collect::<Vec<&str>>();
-                    let result = Command::new(bin.as_str())
-                        .args(args)
-                        .spawn()
-                        .context(format!(
-                            "couldn't run bare command `{}`",
-                            style_stderr!(bold(), "{command}")
-                        ));
-
-                    if let Err(err) = result {
-                        warn_error(&err);
+/// How a [`Job`] is executed.
+#[derive(Debug, Clone)]
+struct Invocation {
+    /// The program to spawn, followed by its arguments.
+    argv: Vec<ImStr>,
+    /// Text to write to stdin, such as the shell command if `config.shell` is piped.
+    stdin: Option<ImStr>,
+    cwd: Option<PathBuf>,
+    /// Environment variables to set, from both `config.env` and the entry.
+    env: Vec<(ImStr, ImStr)>,
+}
+
+impl Invocation {
+    /// Determine how `job` would be executed, or `None` if there's nothing to execute.
+    fn try_new(job: &Job, config: &Config) -> anyhow::Result<Option<Self>> {
+        let invocation = match &job.run {
+            Run::Bare(run) if run.is_empty() => return Ok(None),
+            Run::Bare(run) => Self {
+                argv: run.clone(),
+                stdin: None,
+                cwd: None,
+                env: Vec::new(),
+            },
+            Run::Shell(run) if run.is_empty() => return Ok(None),
+            Run::Shell(run) => match &config.shell {
+                Shell::Disabled => {
+                    return Err(anyhow!(
+                        "shell execution is disabled; to enable, set `config.shell = true`"
+                    )
+                    .context(format!(
+                        "can't execute shell command `{}`",
+                        style_stderr!(bold(), "{run}")
+                    )))
+                }
+                Shell::Enabled { shell, .. } if shell.is_empty() => return Ok(None),
+                Shell::Enabled { shell, piped } => {
+                    let mut argv = shell.clone();
+                    let stdin = if *piped {
+                        Some(run.clone())
+                    } else {
+                        argv.push(run.clone());
+                        None
+                    };
+
+                    Self {
+                        argv,
+                        stdin,
+                        cwd: None,
+                        env: Vec::new(),
                     }
                 }
+            },
+            Run::Copy(text) => Self {
+                argv: config.clipboard.command(),
+                stdin: Some(text.clone()),
+                cwd: None,
+                env: Vec::new(),
+            },
+            Run::Type(text) => {
+                let argv: &[&str] = if is_wayland() {
+                    &["wtype", "-"]
+                } else {
+                    &["xdotool", "type", "--clearmodifiers", "--file", "-"]
+                };
+                Self {
+                    argv: argv.iter().copied().map(ImStr::from).collect(),
+                    stdin: Some(text.clone()),
+                    cwd: None,
+                    env: Vec::new(),
+                }
             }
-            Run::Shell(run) => {
-                if !run.is_empty() {
-                    match &config.shell {
-                        Shell::Disabled => {
-                            let err = anyhow!(
-                                "shell execution is disabled; to enable, set `config.shell = true`"
-                            )
-                            .context(format!(
-                                "can't execute shell command `{}`",
-                                style_stderr!(bold(), "{run}")
-                            ));
-
-                            warn_error(&err);
-                        }
-                        Shell::Enabled { shell, piped } => {
-                            if let Some(shell_name) = shell.first() {
-                                let args =
-                                    &shell[1..].iter().map(ImStr::as_str).collect::<Vec<&str>>();
-                                if *piped {
-                                    let mut shell = Command::new(shell_name.as_str())
-                                        .args(args)
-                                        .stdin(Stdio::piped())
-                                        .stdout(Stdio::piped())
-                                        .stderr(Stdio::piped())
-                                        .spawn()
-                                        .context(format!(
-                                            "failed to run shell `{}` (is it installed?)",
-                                            style_stderr!(bold(), "{shell_name}")
-                                        ))?;
-                                    let mut stdin = shell
-                                        .stdin
-                                        .take()
-                                        .context("failed to establish pipe to shell??")?;
-
-                                    stdin
-                                        .write_all(run.as_bytes())
-                                        .context("failed to write to shell stdin??")?;
-                                } else {
-                                    let result = Command::new(shell_name.as_str())
-                                        .args(args)
-                                        .arg(run.as_str())
-                                        .spawn()
-                                        .context(format!(
-                                            "problem running shell command `{}`",
-                                            style_stderr!(bold(), "{run}")
-                                        ));
-
-                                    if let Err(err) = result {
-                                        warn_error(&err);
-                                    }
-                                }
-                            }
-                        }
+            Run::Open(target) if target.is_empty() => return Ok(None),
+            Run::Open(target) => Self {
+                argv: vec![ImStr::new("xdg-open"), target.clone()],
+                stdin: None,
+                cwd: None,
+                env: Vec::new(),
+            },
+        };
+
+        let env = config
+            .env
+            .0
+            .iter()
+            .filter(|(name, _)| !job.options.env.iter().any(|(set, _)| set == name))
+            .chain(&job.options.env)
+            .cloned()
+            .collect();
+        let invocation = Self {
+            cwd: job.options.cwd.as_ref().map(|cwd| expand_home(config, cwd)),
+            env,
+            ..invocation
+        };
+
+        if !job.options.terminal {
+            return Ok(Some(invocation));
+        }
+
+        let terminal_error = || {
+            format!(
+                "can't run `{}` in a terminal",
+                style_stderr!(bold(), "{}", job.run)
+            )
+        };
+        if invocation.stdin.is_some() {
+            return Err(anyhow!(
+                "`{}` reads from stdin, which the terminal doesn't forward",
+                style_stderr!(bold(), "{}", invocation.argv[0])
+            )
+            .context(terminal_error()));
+        }
+
+        let mut argv = config.terminal.command().with_context(terminal_error)?;
+        argv.extend(invocation.argv);
+
+        Ok(Some(Self { argv, ..invocation }))
+    }
+
+    fn argv(&self) -> Vec<&str> {
+        self.argv.iter().map(ImStr::as_str).collect()
+    }
+
+    /// A [`Command`] that spawns the program with its arguments, working directory, and environment.
+    fn command(&self) -> Command {
+        let (program, args) = self.argv.split_first().expect("argv is never empty");
+        let mut command = Command::new(program.as_str());
+        command.args(args.iter().map(ImStr::as_str)).envs(
+            self.env
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        );
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+
+    /// Spawn the command, waiting for it to exit if `job` has `wait` set.
+    fn spawn(&self, job: &Job, config: &Config) -> anyhow::Result<()> {
+        let run = &job.run;
+        let program = &self.argv[0];
+        let mut command = self.command();
+
+        let output = match &config.detach {
+            Detach::Enabled { log: Some(log) } => Some(open_log(&expand_home(config, log))?),
+            Detach::Enabled { log: None } => Some((Stdio::null(), Stdio::null())),
+            Detach::Disabled => None,
+        };
+        let stdin = if self.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+
+        let detached = if job.options.wait {
+            // Stderr is captured to report why the command failed.
+            if let Some((stdout, _)) = output {
+                command.stdin(stdin).stdout(stdout);
+            } else if self.stdin.is_some() {
+                command.stdin(stdin);
+            }
+            command.stderr(Stdio::piped());
+            false
+        } else if let Some((stdout, stderr)) = output {
+            command.stdin(stdin).stdout(stdout).stderr(stderr);
+            detach(&mut command);
+            true
+        } else {
+            if self.stdin.is_some() {
+                command
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped());
+            }
+            false
+        };
+
+        let result = command.spawn();
+        let mut child = match (run, &self.stdin) {
+            (Run::Shell(_), Some(_)) => result.context(format!(
+                "failed to run shell `{}` (is it installed?)",
+                style_stderr!(bold(), "{program}")
+            )),
+            (Run::Copy(_) | Run::Type(_) | Run::Open(_), _) => result
+                .context(format!(
+                    "failed to run `{}` (is it installed?)",
+                    style_stderr!(bold(), "{program}")
+                ))
+                .context(format!("can't {}", style_stderr!(bold(), "{run}"))),
+            (Run::Bare(_), _) => result.context(format!(
+                "couldn't run bare command `{}`",
+                style_stderr!(bold(), "{run}")
+            )),
+            (Run::Shell(_), None) => result.context(format!(
+                "problem running shell command `{}`",
+                style_stderr!(bold(), "{run}")
+            )),
+        }?;
+
+        if let Some(input) = &self.stdin {
+            let mut stdin = child
+                .stdin
+                .take()
+                .context(format!("failed to establish pipe to {program}??"))?;
+
+            stdin
+                .write_all(input.as_bytes())
+                .context(format!("failed to write to {program} stdin??"))?;
+        }
+
+        if job.options.wait {
+            let output = child.wait_with_output().context(format!(
+                "failed to wait for `{}`",
+                style_stderr!(bold(), "{run}")
+            ))?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let err = match stderr.trim() {
+                    "" => anyhow!("{}", output.status),
+                    stderr => anyhow!("{stderr}").context(output.status),
+                };
+                return Err(err.context(format!("`{}` failed", style_stderr!(bold(), "{run}"))));
+            }
+        } else if detached {
+            // The detached command is orphaned once the intermediate process exits.
+            child
+                .wait()
+                .context("failed to wait for intermediate process??")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Notify the user that `run` failed with `config.notify`,
+/// passing a summary and the root cause of `err` as the last two arguments.
+fn notify(config: &Config, run: &Run, err: &anyhow::Error) {
+    let Notify::Enabled(notifier) = &config.notify else {
+        return;
+    };
+    let Some((program, args)) = notifier.split_first() else {
+        return;
+    };
+
+    let result = Command::new(program.as_str())
+        .args(args.iter().map(ImStr::as_str))
+        .arg(format!("dmm: `{run}` failed"))
+        .arg(err.root_cause().to_string())
+        .stdin(Stdio::null())
+        .status()
+        .context(format!(
+            "failed to run notifier `{}`",
+            style_stderr!(bold(), "{program}")
+        ));
+
+    match result {
+        Ok(status) if !status.success() => warn_error(&anyhow!(
+            "notifier `{}` failed with {status}",
+            style_stderr!(bold(), "{program}")
+        )),
+        Ok(_) => {}
+        Err(err) => warn_error(&err),
+    }
+}
+
+/// Make `command` run in a new session as an orphan,
+/// so it isn't tied to dmm, its process group, or its controlling terminal.
+fn detach(command: &mut Command) {
+    // SAFETY: only async-signal-safe functions are called between fork and exec.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            // Fork again so the command isn't a session leader, and is adopted by init
+            // as soon as the intermediate process exits.
+            match libc::fork() {
+                -1 => Err(io::Error::last_os_error()),
+                0 => Ok(()),
+                _ => libc::_exit(0),
+            }
+        });
+    }
+}
+
+/// Open the log file for the stdout and stderr of detached commands, appending to any existing log.
+fn open_log(path: &Path) -> anyhow::Result<(Stdio, Stdio)> {
+    let open = || -> io::Result<(Stdio, Stdio)> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let log = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok((Stdio::from(log.try_clone()?), Stdio::from(log)))
+    };
+
+    open().context(format!(
+        "unable to open log file `{}`",
+        style_stderr!(bold(), "{}", path.display())
+    ))
+}
+
+fn run_commands(commands: &[Job], config: &Config) {
+    for job in commands {
+        match Invocation::try_new(job, config) {
+            Ok(Some(invocation)) => {
+                if let Err(err) = invocation.spawn(job, config) {
+                    warn_error(&err);
+                    if job.options.wait {
+                        notify(config, &job.run, &err);
                     }
                 }
             }
+            Ok(None) => {}
+            Err(err) => warn_error(&err),
         }
     }
+}
 
-    Ok(())
+/// Write the selected commands to stdout, as text or as json lines.
+fn print_commands(commands: &[Job], config: &Config, format: &str) {
+    for job in commands {
+        if format == "text" {
+            println!("{}", job.run);
+            continue;
+        }
+
+        match Invocation::try_new(job, config) {
+            Ok(Some(invocation)) => {
+                let json = serde_json::json!({
+                    "run": job.run.to_string(),
+                    "kind": job.run.kind(),
+                    "argv": invocation.argv(),
+                    "stdin": invocation.stdin.as_deref(),
+                    "wait": job.options.wait,
+                    "output": match &job.options.output {
+                        Output::Menu { .. } => Some("menu"),
+                        Output::Ignore => None,
+                    },
+                    "cwd": invocation.cwd,
+                    "env": invocation
+                        .env
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.as_str().into()))
+                        .collect::<serde_json::Map<String, serde_json::Value>>(),
+                });
+                println!("{json}");
+            }
+            Ok(None) => {}
+            Err(err) => warn_error(&err),
+        }
+    }
+}
+
+/// Describe how each selected command would be executed, without executing it.
+fn log_commands(commands: &[Job], config: &Config) {
+    let mut stderr = StandardStream::stderr(stderr_color_choice());
+
+    for job in commands {
+        let invocation = match Invocation::try_new(job, config) {
+            Ok(Some(invocation)) => invocation,
+            Ok(None) => continue,
+            Err(err) => {
+                warn_error(&err);
+                continue;
+            }
+        };
+
+        write_style!(stderr, bold(), "dry run: ");
+        let argv = style_stderr!(bold(), "{:?}", invocation.argv());
+        match (&job.run, &invocation.stdin) {
+            (Run::Bare(_), _) => eprint!("would run bare command {argv}"),
+            (Run::Shell(run), Some(_)) => eprint!(
+                "would pipe shell command `{}` to {argv}",
+                style_stderr!(bold(), "{run}")
+            ),
+            (Run::Shell(run), None) => eprint!(
+                "would run shell command `{}` as {argv}",
+                style_stderr!(bold(), "{run}")
+            ),
+            (Run::Copy(text) | Run::Type(text) | Run::Open(text), _) => eprint!(
+                "would {} {} with {argv}",
+                job.run.kind(),
+                style_stderr!(bold(), "{:?}", text.as_str())
+            ),
+        }
+        if let Some(cwd) = &invocation.cwd {
+            eprint!(" in `{}`", style_stderr!(bold(), "{}", cwd.display()));
+        }
+        for (i, (name, value)) in invocation.env.iter().enumerate() {
+            let separator = if i == 0 { " with " } else { ", " };
+            eprint!("{separator}`{}`", style_stderr!(bold(), "{name}={value}"));
+        }
+        if job.options.wait {
+            eprint!(", then wait for it to exit");
+        }
+        if let Output::Menu { each } = &job.options.output {
+            eprint!(", then show its output in a menu");
+            if let Some(each) = each {
+                eprint!(
+                    " and run `{}` on the selected lines",
+                    style_stderr!(bold(), "{each}")
+                );
+            }
+        }
+        eprintln!();
+    }
 }
 
 fn display_error(err: &anyhow::Error) {
@@ -0,0 +1,176 @@
+use std::fmt::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ahash::{HashMap, HashSet};
+use anyhow::Context;
+
+use crate::imstr::ImStr;
+use crate::style::{bold, style_stderr};
+
+/// The executables and subdirectories found in a single directory.
+///
+/// Names are relative to the directory they were found in.
+#[derive(Debug, Default, Clone)]
+pub struct DirListing {
+    pub executables: Vec<ImStr>,
+    pub subdirs: Vec<ImStr>,
+}
+
+/// Directory listings from previous scans of `config.path`,
+/// each tagged with the modification time of its directory when it was scanned.
+///
+/// A listing is only reused if its directory's modification time hasn't changed.
+/// Since changing a file's permissions doesn't modify its directory,
+/// a file that becomes executable won't be found until something else changes the directory.
+///
+/// Stored as a line of `dir<TAB>seconds<TAB>nanoseconds<TAB>path` for each directory,
+/// followed by a line of `x<TAB>name` for each executable and `d<TAB>name` for each subdirectory.
+#[derive(Debug, Default, Clone)]
+pub struct PathCache {
+    dirs: HashMap<PathBuf, (Duration, DirListing)>,
+    visited: HashSet<PathBuf>,
+    changed: bool,
+}
+
+impl PathCache {
+    /// Read the cache at `path`; a missing file is an empty cache.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let cache = match fs::read_to_string(path) {
+            Ok(cache) => cache,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).context(format!(
+                    "unable to read path cache `{}`",
+                    style_stderr!(bold(), "{}", path.display())
+                ))
+            }
+        };
+
+        let mut dirs = HashMap::default();
+        let mut current = None;
+        for line in cache.lines() {
+            let mut fields = line.splitn(2, '\t');
+            let (Some(kind), Some(rest)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+
+            match kind {
+                "dir" => {
+                    let mut fields = rest.splitn(3, '\t');
+                    let secs = fields.next().and_then(|secs| secs.parse().ok());
+                    let nanos = fields.next().and_then(|nanos| nanos.parse().ok());
+                    current = match (secs, nanos, fields.next()) {
+                        (Some(secs), Some(nanos), Some(dir)) => {
+                            let modified = Duration::new(secs, nanos);
+                            let dir = PathBuf::from(dir);
+                            dirs.insert(dir.clone(), (modified, DirListing::default()));
+                            Some(dir)
+                        }
+                        _ => None,
+                    };
+                }
+                "x" | "d" => {
+                    let Some((_, listing)) = current.as_ref().and_then(|dir| dirs.get_mut(dir))
+                    else {
+                        continue;
+                    };
+
+                    if kind == "x" {
+                        listing.executables.push(ImStr::from(rest));
+                    } else {
+                        listing.subdirs.push(ImStr::from(rest));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            dirs,
+            ..Self::default()
+        })
+    }
+
+    /// Write the cache to `path` if anything changed since it was loaded.
+    ///
+    /// Listings of directories that weren't used and no longer exist are dropped.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        let mut cache = String::new();
+        for (dir, (modified, listing)) in &self.dirs {
+            if !self.visited.contains(dir) && !dir.is_dir() {
+                continue;
+            }
+            let Some(dir) = dir.to_str() else {
+                continue;
+            };
+
+            writeln!(
+                cache,
+                "dir\t{}\t{}\t{dir}",
+                modified.as_secs(),
+                modified.subsec_nanos()
+            )
+            .unwrap();
+            for executable in &listing.executables {
+                writeln!(cache, "x\t{executable}").unwrap();
+            }
+            for subdir in &listing.subdirs {
+                writeln!(cache, "d\t{subdir}").unwrap();
+            }
+        }
+
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, cache)?;
+            fs::rename(&tmp, path)
+        };
+
+        write().context(format!(
+            "unable to write path cache `{}`",
+            style_stderr!(bold(), "{}", path.display())
+        ))
+    }
+
+    /// Get the cached listing of `dir` if it hasn't been modified since it was cached.
+    pub fn get(&mut self, dir: &Path, modified: SystemTime) -> Option<&DirListing> {
+        let modified = modified.duration_since(UNIX_EPOCH).ok()?;
+        match self.dirs.get(dir) {
+            Some((cached, listing)) if *cached == modified => {
+                self.visited.insert(dir.to_owned());
+                Some(listing)
+            }
+            _ => None,
+        }
+    }
+
+    /// Cache the `listing` of `dir`, replacing any previous listing.
+    ///
+    /// Listings that can't be stored faithfully, such as names containing newlines, are ignored.
+    pub fn insert(&mut self, dir: PathBuf, modified: SystemTime, listing: DirListing) {
+        let Ok(modified) = modified.duration_since(UNIX_EPOCH) else {
+            return;
+        };
+        let representable = dir.to_str().is_some_and(|dir| !dir.contains('\n'))
+            && listing
+                .executables
+                .iter()
+                .chain(&listing.subdirs)
+                .all(|name| !name.contains('\n'));
+
+        if representable {
+            self.visited.insert(dir.clone());
+            self.dirs.insert(dir, (modified, listing));
+            self.changed = true;
+        }
+    }
+}
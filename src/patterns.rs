@@ -0,0 +1,70 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use toml::Value;
+
+use crate::imstr::ImStr;
+use crate::style::{bold, style_stderr};
+
+/// A pattern that may be chosen with `--pick`.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub path: PathBuf,
+    /// The pattern's `title`, or else its file name without the extension.
+    pub title: ImStr,
+    pub description: Option<ImStr>,
+}
+
+impl Pattern {
+    /// The text shown for the pattern in the launcher.
+    pub fn display(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{} — {description}", self.title),
+            None => self.title.to_string(),
+        }
+    }
+}
+
+/// Find every `.toml` pattern in `dir`, sorted by title, then by path.
+///
+/// A pattern that can't be read or parsed is still listed by its file name,
+/// so the problem is reported if it's chosen.
+pub fn find(dir: &Path) -> anyhow::Result<Vec<Pattern>> {
+    let read = fs::read_dir(dir).context(format!(
+        "unable to read pattern directory `{}`",
+        style_stderr!(bold(), "{}", dir.display())
+    ))?;
+
+    let mut patterns = read
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .map(|path| {
+            let pattern = fs::read_to_string(&path)
+                .ok()
+                .and_then(|pattern| pattern.parse::<Value>().ok());
+            let metadata = |key| {
+                pattern
+                    .as_ref()
+                    .and_then(|pattern| pattern.get(key))
+                    .and_then(Value::as_str)
+                    .map(ImStr::from)
+            };
+
+            let title = metadata("title").unwrap_or_else(|| {
+                let stem = path.file_stem().unwrap_or_default();
+                ImStr::from(stem.to_string_lossy().as_ref())
+            });
+            let description = metadata("description");
+            Pattern {
+                path,
+                title,
+                description,
+            }
+        })
+        .collect::<Vec<Pattern>>();
+
+    patterns.sort_by(|left, right| (&left.title, &left.path).cmp(&(&right.title, &right.path)));
+    Ok(patterns)
+}
@@ -23,6 +23,14 @@ macro_rules! with_buf {
     }
 }
 
+/// The number of characters in `string` that are displayed, ignoring those of invisible tags.
+pub fn visible_width(string: &str) -> usize {
+    string
+        .chars()
+        .filter(|c| !matches!(*c, ZERO | ONE | SEP))
+        .count()
+}
+
 /// Convert a number to a string tag, and convert that tag back to its numeric value.
 pub trait Tag {
     /// Convert a number to a tag that is pushed onto the provided [`String`].